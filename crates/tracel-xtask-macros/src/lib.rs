@@ -78,7 +78,11 @@ fn generate_dispatch_function(
     let arms: Vec<proc_macro2::TokenStream> = args.iter().map(|meta| {
         let cmd_ident = meta.path().get_ident().unwrap();
         let cmd_ident_string = cmd_ident.to_string();
-        let module_ident = syn::Ident::new(cmd_ident_string.to_lowercase().as_str(), cmd_ident.span());
+        let module_name = match cmd_ident_string.as_str() {
+            "InstallHooks" => "hooks".to_string(),
+            _ => cmd_ident_string.to_lowercase(),
+        };
+        let module_ident = syn::Ident::new(module_name.as_str(), cmd_ident.span());
         match cmd_ident_string.as_str() {
             "Fix" => quote! {
                 #enum_ident::#cmd_ident(args) => base_commands::#module_ident::handle_command(args, None),
@@ -107,6 +111,13 @@ pub fn base_commands(args: TokenStream, input: TokenStream) -> TokenStream {
 
     // Supported commands and their quoted expansions
     let mut variant_map: HashMap<&str, proc_macro2::TokenStream> = HashMap::new();
+    variant_map.insert(
+        "Bench",
+        quote! {
+            #[doc = r"Run the benchmarks."]
+            Bench(tracel_xtask::commands::bench::BenchCmdArgs)
+        },
+    );
     variant_map.insert(
         "Build",
         quote! {
@@ -135,6 +146,13 @@ pub fn base_commands(args: TokenStream, input: TokenStream) -> TokenStream {
             Check(tracel_xtask::commands::check::CheckCmdArgs)
         },
     );
+    variant_map.insert(
+        "Clean",
+        quote! {
+            #[doc = r"Remove the build artifacts."]
+            Clean(tracel_xtask::commands::clean::CleanCmdArgs)
+        },
+    );
     variant_map.insert(
         "Compile",
         quote! {
@@ -163,6 +181,13 @@ pub fn base_commands(args: TokenStream, input: TokenStream) -> TokenStream {
             Dependencies(tracel_xtask::commands::dependencies::DependenciesCmdArgs)
         },
     );
+    variant_map.insert(
+        "InstallHooks",
+        quote! {
+            #[doc = r"Install git hooks running 'validate --profile fast' before pushing."]
+            InstallHooks(tracel_xtask::commands::hooks::InstallHooksCmdArgs)
+        },
+    );
     variant_map.insert(
         "Publish",
         quote! {
@@ -234,25 +259,168 @@ pub fn base_commands(args: TokenStream, input: TokenStream) -> TokenStream {
 
 fn get_additional_cmd_args_map() -> HashMap<&'static str, proc_macro2::TokenStream> {
     HashMap::from([
+        (
+            "BumpCmdArgs",
+            quote! {
+                #[doc = r"Only bump the [workspace.package] version of the root manifest (detected automatically when members inherit it)."]
+                #[arg(long = "workspace-version", required = false)]
+                pub workspace_version: bool,
+                #[doc = r"Print the current and proposed version of each crate without modifying any manifest."]
+                #[arg(long = "dry-run", required = false)]
+                pub dry_run: bool,
+                #[doc = r"Build metadata to append to the new version (x.y.z+METADATA)."]
+                #[arg(long, value_name = "STR", required = false)]
+                pub metadata: Option<String>,
+            },
+        ),
+        (
+            "BenchCmdArgs",
+            quote! {
+                #[doc = r"Only run the benchmark target with this name."]
+                #[arg(long, value_name = "NAME")]
+                pub bench: Option<String>,
+            },
+        ),
+        (
+            "CleanCmdArgs",
+            quote! {
+                #[doc = r"Only remove the documentation."]
+                #[arg(long, conflicts_with = "release", required = false)]
+                pub doc: bool,
+                #[doc = r"Only remove the artifacts of the release profile."]
+                #[arg(long, required = false)]
+                pub release: bool,
+            },
+        ),
+        (
+            "BuildCmdArgs",
+            quote! {
+                #[doc = r"Print the compiler diagnostics of the workspace as 'file:line:col: level: message' lines instead of the usual output."]
+                #[arg(long = "compact-diagnostics", required = false)]
+                pub compact_diagnostics: bool,
+                #[doc = r"Enable all the features of the packages."]
+                #[arg(long = "all-features", required = false)]
+                pub all_features: bool,
+                #[doc = r"Build each feature or each combination of features of the packages using cargo-hack."]
+                #[arg(long, value_enum, conflicts_with_all = ["all_features", "compact_diagnostics"])]
+                pub hack: Option<tracel_xtask::commands::HackMode>,
+                #[doc = r"Build for the given target triple, installed with rustup if needed, can be repeated."]
+                #[arg(long = "target-triple", value_name = "TRIPLE", conflicts_with = "compact_diagnostics")]
+                pub target_triple: Vec<String>,
+            },
+        ),
         (
             "CheckCmdArgs",
             quote! {
                 #[doc = r"Ignore audit errors."]
                 #[arg(long = "ignore-audit", required = false)]
                 pub ignore_audit: bool,
+                #[doc = r"JSON file of the clippy warning counts per crate for 'lint', written unless '--check-baseline' is set."]
+                #[arg(long, value_name = "PATH")]
+                pub baseline: Option<std::path::PathBuf>,
+                #[doc = r"Fail 'lint' if a crate has more clippy warnings than recorded in the '--baseline' file."]
+                #[arg(long = "check-baseline", requires = "baseline")]
+                pub check_baseline: bool,
+                #[doc = r"Directory of the public API of each crate for 'pub-check', written unless '--check-api-baseline' is set."]
+                #[arg(long = "api-baseline", value_name = "DIR")]
+                pub api_baseline: Option<std::path::PathBuf>,
+                #[doc = r"Fail 'pub-check' if a public API has items which are not in the '--api-baseline' directory."]
+                #[arg(long = "check-api-baseline", requires = "api_baseline")]
+                pub check_api_baseline: bool,
+                #[doc = r"Print the clippy diagnostics of 'lint' as 'file:line:col: level: message' lines instead of the usual output."]
+                #[arg(long = "compact-diagnostics", conflicts_with = "baseline", required = false)]
+                pub compact_diagnostics: bool,
+                #[doc = r"Fail 'manifest-lint' when issues are found instead of only reporting them."]
+                #[arg(long, required = false)]
+                pub strict: bool,
+                #[doc = r"Enable all the features of the packages."]
+                #[arg(long = "all-features", required = false)]
+                pub all_features: bool,
+                #[doc = r"Only run 'format', 'lint' and 'manifest-lint' on the packages with changes since the given git reference."]
+                #[arg(long = "changed-since", value_name = "REF", conflicts_with_all = ["baseline", "compact_diagnostics"])]
+                pub changed_since: Option<String>,
+                #[doc = r"Run all the checks of 'all' even if some of them fail and report the failed ones at the end."]
+                #[arg(long = "keep-going", required = false)]
+                pub keep_going: bool,
+                #[doc = r"Run 'lint' for each feature or each combination of features of the packages using cargo-hack."]
+                #[arg(long, value_enum, conflicts_with_all = ["all_features", "baseline", "compact_diagnostics"])]
+                pub hack: Option<tracel_xtask::commands::HackMode>,
+                #[doc = r"Path of the typos configuration file to use instead of the discovered one."]
+                #[arg(long = "typos-config", value_name = "PATH", required = false)]
+                pub typos_config: Option<std::path::PathBuf>,
+            },
+        ),
+        (
+            "DocCmdArgs",
+            quote! {
+                #[doc = r"Fail the build if a publishable crate has undocumented public items."]
+                #[arg(long = "require-docs", required = false)]
+                pub require_docs: bool,
+                #[doc = r"Fail the 'build' subcommand if rustdoc reports more than this number of warnings."]
+                #[arg(long = "max-warnings", value_name = "N", required = false)]
+                pub max_warnings: Option<usize>,
+                #[doc = r"Deny rustdoc warnings (missing docs, broken intra-doc links, ...) in the 'build' and 'tests' subcommands."]
+                #[arg(long = "deny-warnings", conflicts_with = "max_warnings", required = false)]
+                pub deny_warnings: bool,
+                #[doc = r"Copy the documentation generated by the 'build' subcommand to this directory."]
+                #[arg(long = "output-dir", value_name = "DIR", required = false)]
+                pub output_dir: Option<std::path::PathBuf>,
+                #[doc = r"Open the documentation generated by the 'build' subcommand in a browser, printed on headless environments."]
+                #[arg(long, required = false)]
+                pub open: bool,
+                #[doc = r"Comma-separated list of features to enable for the 'build' and 'tests' subcommands."]
+                #[arg(
+                    long,
+                    value_name = "FEATURE,FEATURE,...",
+                    value_delimiter = ',',
+                    required = false
+                )]
+                pub features: Option<Vec<String>>,
+                #[doc = r"If set, ignore default features for the 'build' and 'tests' subcommands."]
+                #[arg(long = "no-default-features", required = false)]
+                pub no_default_features: bool,
+                #[doc = r"Enable all the features of the packages."]
+                #[arg(
+                    long = "all-features",
+                    conflicts_with_all = ["features", "no_default_features"],
+                    required = false
+                )]
+                pub all_features: bool,
+            },
+        ),
+        (
+            "FixCmdArgs",
+            quote! {
+                #[doc = r"Path of the typos configuration file to use instead of the discovered one."]
+                #[arg(long = "typos-config", value_name = "PATH", required = false)]
+                pub typos_config: Option<std::path::PathBuf>,
+                #[doc = r"Only fix the Rust files changed since the given git reference, 'format' runs rustfmt on these files and 'lint' runs on the packages owning them."]
+                #[arg(long, value_name = "GIT_REF", required = false)]
+                pub since: Option<String>,
+            },
+        ),
+        (
+            "InstallHooksCmdArgs",
+            quote! {
+                #[doc = r"Also install a pre-commit hook."]
+                #[arg(long, conflicts_with = "uninstall")]
+                pub pre_commit: bool,
+                #[doc = r"Remove the installed hooks and restore the previous ones."]
+                #[arg(long)]
+                pub uninstall: bool,
             },
         ),
         (
             "TestCmdArgs",
             quote! {
-                #[doc = r"Maximum number of parallel test crate compilations."]
+                #[doc = r"Maximum number of parallel test crate compilations (forwarded to cargo as '--jobs', to nextest as '--build-jobs')."]
                 #[arg(
                     long = "compilation-jobs",
                     value_name = "NUMBER OF THREADS",
                     required = false
                 )]
                 pub jobs: Option<u16>,
-                #[doc = r"Maximum number of parallel test within a test crate execution."]
+                #[doc = r"Maximum number of parallel test within a test crate execution (forwarded to the test harness as '--test-threads')."]
                 #[arg(
                     long = "test-threads",
                     value_name = "NUMBER OF THREADS",
@@ -273,6 +441,75 @@ fn get_additional_cmd_args_map() -> HashMap<&'static str, proc_macro2::TokenStre
                     required = false
                 )]
                 pub no_default_features: bool,
+                #[doc = r"Enable all the features of the packages."]
+                #[arg(
+                    long = "all-features",
+                    conflicts_with_all = ["features", "no_default_features", "package_features"],
+                    required = false
+                )]
+                pub all_features: bool,
+                #[doc = r"Backend used to execute the tests."]
+                #[arg(long, value_enum, default_value_t = tracel_xtask::commands::test::TestRunner::default())]
+                pub runner: tracel_xtask::commands::test::TestRunner,
+                #[doc = r"Write the test results to this path as a JUnit XML report, requires 'cargo +nightly'."]
+                #[arg(long, value_name = "PATH")]
+                pub junit: Option<std::path::PathBuf>,
+                #[doc = r"Re-run the test binaries with failed tests up to this number of times, the passing test binaries are not executed again. With nextest only the failing tests are retried."]
+                #[arg(long, value_name = "N", default_value_t = 0)]
+                pub retries: u16,
+                #[doc = r"Test each feature or each combination of features of the packages using cargo-hack."]
+                #[arg(long, value_enum, conflicts_with = "all_features")]
+                pub hack: Option<tracel_xtask::commands::HackMode>,
+                #[doc = r"Features to enable only for the given crate, can be repeated for several crates."]
+                #[arg(
+                    long = "package-features",
+                    value_name = "CRATE=FEATURE,FEATURE,...",
+                    required = false
+                )]
+                pub package_features: Vec<String>,
+                #[doc = r"Authorize to execute tests in production environment."]
+                #[arg(long, required = false)]
+                pub force: bool,
+                #[doc = r"Show the output of the tests as they run instead of capturing it."]
+                #[arg(long = "no-capture", alias = "nocapture", required = false)]
+                pub no_capture: bool,
+                #[doc = r"Run only the tests marked with #[ignore]."]
+                #[arg(long, conflicts_with = "include_ignored", required = false)]
+                pub ignored: bool,
+                #[doc = r"Run the tests marked with #[ignore] along with the other tests."]
+                #[arg(long = "include-ignored", required = false)]
+                pub include_ignored: bool,
+                #[doc = r"Only run the tests whose name contains this filter, can be repeated."]
+                #[arg(long = "test", value_name = "NAME", required = false)]
+                pub test: Vec<String>,
+                #[doc = r"Only run the tests whose name is exactly one of the '--test' filters."]
+                #[arg(long, requires = "test", required = false)]
+                pub exact: bool,
+                #[doc = r"Extra argument passed to the test binaries after '--', can be repeated."]
+                #[arg(
+                    long = "binary-arg",
+                    value_name = "ARG",
+                    allow_hyphen_values = true,
+                    required = false
+                )]
+                pub binary_args: Vec<String>,
+                #[doc = r"Instrument the tests for coverage and generate the 'lcov.info' report once they pass."]
+                #[arg(long, required = false)]
+                pub coverage: bool,
+                #[doc = r"Build and run the tests with the release profile, '--coverage' then reports on the release binaries."]
+                #[arg(long, required = false)]
+                pub release: bool,
+            },
+        ),
+        (
+            "VulnerabilitiesCmdArgs",
+            quote! {
+                #[doc = r"Write an HTML report of the checks results to the given path."]
+                #[arg(long, value_name = "PATH", required = false)]
+                pub report: Option<std::path::PathBuf>,
+                #[doc = r"Open the HTML report once generated (requires --report)."]
+                #[arg(long, requires = "report", required = false)]
+                pub open: bool,
             },
         ),
         (
@@ -281,6 +518,15 @@ fn get_additional_cmd_args_map() -> HashMap<&'static str, proc_macro2::TokenStre
                 #[doc = r"Ignore audit errors."]
                 #[arg(long = "ignore-audit", required = false)]
                 pub ignore_audit: bool,
+                #[doc = r"Set of steps to run, 'fast' only runs format, lint and compile."]
+                #[arg(long, value_enum, default_value_t = tracel_xtask::commands::validate::ValidateProfile::default())]
+                pub profile: tracel_xtask::commands::validate::ValidateProfile,
+                #[doc = r"Check the workspace against the powerset of its features using cargo-hack."]
+                #[arg(long = "feature-powerset", required = false)]
+                pub feature_powerset: bool,
+                #[doc = r"Maximum number of features combined together when checking the feature powerset."]
+                #[arg(long = "powerset-depth", default_value_t = 2, requires = "feature_powerset")]
+                pub powerset_depth: usize,
             },
         ),
     ])
@@ -374,6 +620,15 @@ fn generate_command_args_struct(
                     required = false
                 )]
                 pub only: Vec<String>,
+                #[doc = r"Pass '--locked' to cargo to require an up-to-date Cargo.lock."]
+                #[arg(long, required = false)]
+                pub locked: bool,
+                #[doc = r"Pass '--frozen' to cargo to require an up-to-date Cargo.lock without network access."]
+                #[arg(long, required = false)]
+                pub frozen: bool,
+                #[doc = r"Pass '--offline' to cargo to run without network access."]
+                #[arg(long, required = false)]
+                pub offline: bool,
             }
         } else {
             quote! {}
@@ -493,11 +748,52 @@ fn generate_command_args_tryinto(args: TokenStream, input: TokenStream) -> Token
                 if ident_str != "target"
                     && (ident_str == "exclude"
                         || ident_str == "features"
+                        || ident_str == "force"
+                        || ident_str == "frozen"
+                        || ident_str == "locked"
                         || ident_str == "no_default_features"
+                        || ident_str == "offline"
                         || ident_str == "only"
+                        || ident_str == "output_dir"
+                        || ident_str == "package_features"
+                        || ident_str == "profile"
                         || ident_str == "ignore_audit"
+                        || ident_str == "ignored"
+                        || ident_str == "include_ignored"
+                        || ident_str == "all_features"
+                        || ident_str == "api_baseline"
+                        || ident_str == "hack"
+                        || ident_str == "baseline"
+                        || ident_str == "bench"
+                        || ident_str == "binary_args"
+                        || ident_str == "changed_since"
+                        || ident_str == "check_api_baseline"
+                        || ident_str == "check_baseline"
+                        || ident_str == "exact"
+                        || ident_str == "strict"
+                        || ident_str == "target_triple"
+                        || ident_str == "workspace_version"
+                        || ident_str == "compact_diagnostics"
+                        || ident_str == "coverage"
+                        || ident_str == "deny_warnings"
+                        || ident_str == "doc"
+                        || ident_str == "dry_run"
                         || ident_str == "jobs"
-                        || ident_str == "threads")
+                        || ident_str == "junit"
+                        || ident_str == "keep_going"
+                        || ident_str == "max_warnings"
+                        || ident_str == "metadata"
+                        || ident_str == "no_capture"
+                        || ident_str == "open"
+                        || ident_str == "release"
+                        || ident_str == "report"
+                        || ident_str == "retries"
+                        || ident_str == "runner"
+                        || ident_str == "require_docs"
+                        || ident_str == "test"
+                        || ident_str == "threads"
+                        || ident_str == "typos_config"
+                        || ident_str == "since")
                 {
                     quote! { #ident: self.#ident, }
                 } else {
@@ -578,6 +874,8 @@ fn get_subcommand_variant_map() -> HashMap<&'static str, proc_macro2::TokenStrea
                 #[default]
                 #[doc = r"Bump the patch version (0.0.x)."]
                 Patch,
+                #[doc = r"Bump to the next prerelease with the given identifier (0.0.x-rc.n)."]
+                Prerelease(tracel_xtask::commands::bump::PrereleaseCmdArgs),
             },
         ),
         (
@@ -592,6 +890,18 @@ fn get_subcommand_variant_map() -> HashMap<&'static str, proc_macro2::TokenStrea
                 Format,
                 #[doc = r"Run lint command."]
                 Lint,
+                #[doc = r"Fail if the committed Cargo.lock is not up to date with the manifests (not part of 'all')."]
+                LockCheck,
+                #[doc = r"Report common issues in the manifests of the workspace members."]
+                ManifestLint,
+                #[doc = r"Build the workspace with the minimal versions of its dependencies, requires 'cargo +nightly' (not part of 'all')."]
+                MinimalVersions,
+                #[doc = r"Verify with cargo-msrv that the crates build with the 'rust-version' of their manifest (not part of 'all')."]
+                Msrv,
+                #[doc = r"Compare the public API of the publishable crates with a baseline (not part of 'all')."]
+                PubCheck,
+                #[doc = r"Detect semver breaking changes of the publishable crates with cargo-semver-checks (not part of 'all')."]
+                SemverCheck,
                 #[doc = r"Report typos in source code."]
                 Typos,
             },
@@ -602,8 +912,10 @@ fn get_subcommand_variant_map() -> HashMap<&'static str, proc_macro2::TokenStrea
             quote! {
                 #[doc = r"Install grcov and its dependencies."]
                 Install,
-                #[doc = r"Generate lcov.info file. [default with default debug profile]"]
+                #[doc = r"Generate the coverage report (lcov.info by default) from the instrumented binaries of the selected profile. [default with default debug profile]"]
                 Generate(GenerateCmdArgs),
+                #[doc = r"Remove the profraw files left by instrumented runs."]
+                Clean(CleanCmdArgs),
             },
         ),
         (
@@ -654,6 +966,8 @@ fn get_subcommand_variant_map() -> HashMap<&'static str, proc_macro2::TokenStrea
                 Unit,
                 #[doc = r"Run integration tests."]
                 Integration,
+                #[doc = r"Run documentation tests (not part of 'all')."]
+                Doc,
             },
         ),
         (
@@ -738,8 +1052,14 @@ fn generate_subcomand_tryinto(
         parse_macro_input!(variants_tokens with Punctuated::<Variant, Comma>::parse_terminated);
     let arms = parsed_variants.iter().map(|v| {
         let variant_ident = &v.ident;
-        quote! {
-            #subcommand::#variant_ident => Ok(#base_subcommand::#variant_ident),
+        if v.fields.is_empty() {
+            quote! {
+                #subcommand::#variant_ident => Ok(#base_subcommand::#variant_ident),
+            }
+        } else {
+            quote! {
+                #subcommand::#variant_ident(args) => Ok(#base_subcommand::#variant_ident(args)),
+            }
         }
     });
     let tryinto = quote! {
@@ -0,0 +1,95 @@
+use anyhow::Ok;
+use strum::IntoEnumIterator;
+
+use crate::{
+    commands::WARN_IGNORED_ONLY_ARGS,
+    endgroup, group,
+    utils::{
+        process::{run_process_for_package, run_process_for_workspace, CargoLockArgs},
+        workspace::{get_workspace_members, WorkspaceMemberType},
+    },
+};
+
+use super::Target;
+
+#[tracel_xtask_macros::declare_command_args(Target, None)]
+pub struct BenchCmdArgs {}
+
+pub fn handle_command(args: BenchCmdArgs) -> anyhow::Result<()> {
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    if args.target == Target::Workspace && !args.only.is_empty() {
+        warn!("{}", WARN_IGNORED_ONLY_ARGS);
+    }
+    run_bench(
+        &args.target,
+        &args.exclude,
+        &args.only,
+        &args.bench,
+        lock_args,
+    )
+}
+
+pub(crate) fn run_bench(
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+    bench: &Option<String>,
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    match target {
+        Target::Workspace => {
+            info!("Workspace Benchmarks");
+            let mut cmd_args = vec!["bench", "--workspace", "--color", "always"];
+            if let Some(bench) = bench {
+                cmd_args.extend(["--bench", bench]);
+            }
+            run_process_for_workspace(
+                "cargo",
+                &lock_args.apply(&cmd_args),
+                None,
+                excluded,
+                Some(r".*target/[^/]+/deps/([^-\s]+)"),
+                Some("Benchmarks"),
+                "Workspace benchmarks failed",
+                None,
+                None,
+            )?;
+        }
+        Target::Crates | Target::Examples => {
+            let members = match target {
+                Target::Crates => get_workspace_members(WorkspaceMemberType::Crate),
+                Target::Examples => get_workspace_members(WorkspaceMemberType::Example),
+                _ => unreachable!(),
+            };
+
+            for member in members {
+                group!("Benchmarks: {}", member.name);
+                let mut cmd_args = vec!["bench", "-p", &member.name, "--color", "always"];
+                if let Some(bench) = bench {
+                    cmd_args.extend(["--bench", bench]);
+                }
+                run_process_for_package(
+                    "cargo",
+                    &member.name,
+                    &lock_args.apply(&cmd_args),
+                    None,
+                    excluded,
+                    only,
+                    &format!("Benchmarks failed for {}", &member.name),
+                    Some("no bench target named"),
+                    Some(&format!(
+                        "No benchmark matching the filter found in the crate '{}'.",
+                        &member.name
+                    )),
+                )?;
+                endgroup!();
+            }
+        }
+        Target::AllPackages => {
+            Target::iter()
+                .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
+                .try_for_each(|t| run_bench(&t, excluded, only, bench, lock_args))?;
+        }
+    }
+    Ok(())
+}
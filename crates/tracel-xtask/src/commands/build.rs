@@ -1,13 +1,26 @@
+use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+};
+
 use anyhow::Ok;
+use serde_json::Value;
 use strum::IntoEnumIterator;
 
 use crate::{
-    commands::WARN_IGNORED_ONLY_ARGS,
-    endgroup, group,
-    utils::{
-        process::{run_process_for_package, run_process_for_workspace},
-        workspace::{get_workspace_members, WorkspaceMemberType},
+    commands::{
+        ensure_hack_is_installed, with_hack_mode, HackMode, WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS,
+        WARN_IGNORED_ONLY_ARGS,
     },
+    endgroup,
+    errors::{process_failed, spawn_error},
+    group,
+    utils::process::{
+        run_process_for_package, run_process_for_workspace, split_vector, with_cargo_extra_args,
+        CargoLockArgs,
+    },
+    utils::rustup::rustup_add_target,
+    utils::workspace::{get_workspace_members, WorkspaceMemberType},
 };
 
 use super::Target;
@@ -16,23 +29,96 @@ use super::Target;
 pub struct BuildCmdArgs {}
 
 pub fn handle_command(args: BuildCmdArgs) -> anyhow::Result<()> {
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    if args.compact_diagnostics {
+        if args.target != Target::Workspace || !args.only.is_empty() {
+            warn!("{}", WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS);
+        }
+        let mut cmd_args = vec!["build", "--workspace"];
+        if args.all_features {
+            cmd_args.push("--all-features");
+        }
+        return run_with_compact_diagnostics(
+            &cmd_args,
+            &args.exclude,
+            lock_args,
+            "Workspace build failed",
+        );
+    }
     if args.target == Target::Workspace && !args.only.is_empty() {
         warn!("{}", WARN_IGNORED_ONLY_ARGS);
     }
-    run_build(&args.target, &args.exclude, &args.only)
+    ensure_hack_is_installed(args.hack)?;
+    if args.target_triple.is_empty() {
+        return run_build(
+            &args.target,
+            &args.exclude,
+            &args.only,
+            args.all_features,
+            args.hack,
+            None,
+            lock_args,
+        );
+    }
+    // build all the triples and report the failed ones at the end
+    let mut failed = vec![];
+    for triple in &args.target_triple {
+        let result = rustup_add_target(triple).and_then(|_| {
+            run_build(
+                &args.target,
+                &args.exclude,
+                &args.only,
+                args.all_features,
+                args.hack,
+                Some(triple),
+                lock_args,
+            )
+        });
+        if let Err(e) = result {
+            error!("Build failed for target triple {}: {}", triple, e);
+            failed.push(triple.as_str());
+        }
+    }
+    for triple in &args.target_triple {
+        let status = if failed.contains(&triple.as_str()) {
+            "failed"
+        } else {
+            "succeeded"
+        };
+        info!("Build for target triple {}: {}", triple, status);
+    }
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Build failed for target triples: {}",
+            failed.join(", ")
+        ));
+    }
+    Ok(())
 }
 
 pub(crate) fn run_build(
     target: &Target,
     excluded: &Vec<String>,
     only: &Vec<String>,
+    all_features: bool,
+    hack: Option<HackMode>,
+    triple: Option<&str>,
+    lock_args: CargoLockArgs,
 ) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Build Workspace");
+            let mut cmd_args = vec!["build", "--workspace", "--color", "always"];
+            if all_features {
+                cmd_args.push("--all-features");
+            }
+            if let Some(triple) = triple {
+                cmd_args.extend(["--target", triple]);
+            }
             run_process_for_workspace(
                 "cargo",
-                &["build", "--workspace", "--color", "always"],
+                &with_hack_mode(&lock_args.apply(&cmd_args), hack),
+                None,
                 excluded,
                 None,
                 None,
@@ -51,10 +137,18 @@ pub(crate) fn run_build(
 
             for member in members {
                 group!("Build: {}", member.name);
+                let mut cmd_args = vec!["build", "-p", &member.name, "--color", "always"];
+                if all_features {
+                    cmd_args.push("--all-features");
+                }
+                if let Some(triple) = triple {
+                    cmd_args.extend(["--target", triple]);
+                }
                 run_process_for_package(
                     "cargo",
                     &member.name,
-                    &["build", "-p", &member.name, "--color", "always"],
+                    &with_hack_mode(&lock_args.apply(&cmd_args), hack),
+                    None,
                     excluded,
                     only,
                     &format!("Build command failed for {}", &member.name),
@@ -67,8 +161,102 @@ pub(crate) fn run_build(
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_build(&t, excluded, only))?;
+                .try_for_each(|t| {
+                    run_build(&t, excluded, only, all_features, hack, triple, lock_args)
+                })?;
         }
     }
     Ok(())
 }
+
+/// Run cargo on the workspace with JSON diagnostics and print each of them on a single
+/// 'file:line:col: level: message' line.
+pub(crate) fn run_with_compact_diagnostics(
+    args: &[&str],
+    excluded: &[String],
+    lock_args: CargoLockArgs,
+    error_msg: &str,
+) -> anyhow::Result<()> {
+    let (cargo_args, binary_args) = split_vector(args, "--");
+    let mut cmd_args = cargo_args.to_vec();
+    cmd_args.push("--message-format=json");
+    excluded
+        .iter()
+        .for_each(|ex| cmd_args.extend(["--exclude", ex]));
+    cmd_args.extend(binary_args);
+    let cmd_args = with_cargo_extra_args(&lock_args.apply(&cmd_args));
+    let mut child = Command::new("cargo")
+        .args(&cmd_args)
+        .stdout(Stdio::piped())
+        // build errors which are not diagnostics (manifest, resolution, ...) are only on stderr
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| {
+            spawn_error(
+                "cargo",
+                e,
+                &format!("Failed to execute cargo {}", cmd_args.join(" ")),
+            )
+        })?;
+    if let Some(stdout) = child.stdout.take() {
+        BufReader::new(stdout)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|l| serde_json::from_str::<Value>(&l).ok())
+            .filter_map(|m| compact_diagnostic(&m))
+            .for_each(|d| println!("{}", d));
+    }
+    let status = child
+        .wait()
+        .map_err(|e| anyhow::anyhow!("Failed to wait for cargo {}: {}", cmd_args.join(" "), e))?;
+    if !status.success() {
+        return Err(process_failed(error_msg));
+    }
+    Ok(())
+}
+
+/// Render a cargo 'compiler-message' JSON message as 'file:line:col: level: message'.
+/// Messages without a source location are rendered as 'level: message'.
+fn compact_diagnostic(message: &Value) -> Option<String> {
+    if message["reason"] != "compiler-message" {
+        return None;
+    }
+    let diagnostic = &message["message"];
+    let level = diagnostic["level"].as_str()?;
+    let text = diagnostic["message"].as_str()?;
+    let primary_span = diagnostic["spans"]
+        .as_array()
+        .and_then(|spans| spans.iter().find(|s| s["is_primary"] == true));
+    match primary_span {
+        Some(span) => Some(format!(
+            "{}:{}:{}: {}: {}",
+            span["file_name"].as_str()?,
+            span["line_start"],
+            span["column_start"],
+            level,
+            text
+        )),
+        None => Some(format!("{}: {}", level, text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::with_span(
+        r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable: `x`","spans":[{"file_name":"src/other.rs","line_start":1,"column_start":1,"is_primary":false},{"file_name":"src/lib.rs","line_start":12,"column_start":9,"is_primary":true}]}}"#,
+        Some("src/lib.rs:12:9: warning: unused variable: `x`")
+    )]
+    #[case::without_span(
+        r#"{"reason":"compiler-message","message":{"level":"error","message":"aborting due to 1 previous error","spans":[]}}"#,
+        Some("error: aborting due to 1 previous error")
+    )]
+    #[case::other_reason(r#"{"reason":"build-finished","success":true}"#, None)]
+    fn test_compact_diagnostic(#[case] message: &str, #[case] expected: Option<&str>) {
+        let message: Value = serde_json::from_str(message).unwrap();
+        assert_eq!(compact_diagnostic(&message).as_deref(), expected);
+    }
+}
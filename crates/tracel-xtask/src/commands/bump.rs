@@ -1,27 +1,480 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Ok;
+use clap::Args;
+use semver::{BuildMetadata, Prerelease, Version};
+use toml_edit::DocumentMut;
 
 use crate::{
     endgroup, group,
-    utils::{cargo::ensure_cargo_crate_is_installed, process::run_process},
+    utils::{
+        cargo::ensure_cargo_crate_is_installed,
+        output::{output_mode, print_result, CommandResult},
+        process::run_process,
+        workspace::{get_workspace_members, get_workspace_root, WorkspaceMemberType},
+    },
 };
 
 #[tracel_xtask_macros::declare_command_args(None, BumpSubCommand)]
 pub struct BumpCmdArgs {}
 
+#[derive(Args, Default, Clone, PartialEq)]
+pub struct PrereleaseCmdArgs {
+    /// Identifier of the prerelease, for instance 'alpha', 'beta' or 'rc'.
+    #[arg(value_name = "IDENTIFIER")]
+    pub identifier: String,
+}
+
 pub fn handle_command(args: BumpCmdArgs) -> anyhow::Result<()> {
-    bump(&args.get_command())
+    let workspace_version = args.workspace_version || uses_workspace_version()?;
+    let metadata = args
+        .metadata
+        .as_deref()
+        .map(|m| {
+            BuildMetadata::new(m)
+                .map_err(|e| anyhow::anyhow!("Invalid build metadata '{}': {}", m, e))
+        })
+        .transpose()?;
+    let command = args.get_command();
+    if args.dry_run {
+        let plan = bump_plan(&command, metadata.as_ref(), workspace_version)?;
+        print_result(&plan, output_mode());
+        info!("Dry run, no manifest has been modified.");
+        Ok(())
+    } else if workspace_version {
+        bump_workspace_version(&command, metadata.as_ref())
+    } else {
+        bump(&command, metadata.as_ref())
+    }
 }
 
-fn bump(command: &BumpSubCommand) -> anyhow::Result<()> {
+fn bump(command: &BumpSubCommand, metadata: Option<&BuildMetadata>) -> anyhow::Result<()> {
     group!("Bump version: {command}");
     ensure_cargo_crate_is_installed("cargo-edit", None, None, false)?;
-    run_process(
-        "cargo",
-        &["set-version", "--bump", &command.to_string()],
-        None,
-        None,
-        &format!("Error trying to bump {command} version"),
-    )?;
+    if let BumpSubCommand::Prerelease(_) = command {
+        // 'cargo set-version --bump' only knows the alpha, beta and rc prereleases so the
+        // version of each package is computed here and set explicitly
+        for change in bump_plan(command, metadata, false)?.0 {
+            let version = change.proposed.to_string();
+            run_process(
+                "cargo",
+                &["set-version", "-p", &change.name, &version],
+                None,
+                None,
+                &format!(
+                    "Error trying to set the version of {} to {version}",
+                    change.name
+                ),
+            )?;
+        }
+    } else {
+        let command_str = command.to_string();
+        let metadata = metadata.map(|m| m.to_string());
+        let mut args = vec!["set-version", "--bump", &command_str];
+        if let Some(metadata) = &metadata {
+            args.extend(["--metadata", metadata]);
+        }
+        run_process(
+            "cargo",
+            &args,
+            None,
+            None,
+            &format!("Error trying to bump {command} version"),
+        )?;
+    }
     endgroup!();
     Ok(())
 }
+
+/// Bump the `[workspace.package]` version of the root manifest, the members inheriting it
+/// with `version.workspace = true` are left untouched.
+fn bump_workspace_version(
+    command: &BumpSubCommand,
+    metadata: Option<&BuildMetadata>,
+) -> anyhow::Result<()> {
+    group!("Bump workspace version: {command}");
+    let root_manifest = root_manifest_path();
+    let manifest = std::fs::read_to_string(&root_manifest)?;
+    let (manifest, version) = bump_workspace_version_in(&manifest, command, metadata)?;
+    std::fs::write(&root_manifest, manifest)?;
+    info!("Workspace version bumped to {version}");
+    endgroup!();
+    Ok(())
+}
+
+/// Return the path of the workspace root manifest, wherever the command is run from.
+fn root_manifest_path() -> PathBuf {
+    get_workspace_root().join("Cargo.toml")
+}
+
+/// Return true if the root manifest defines a `[workspace.package]` version which is inherited
+/// by at least one member.
+fn uses_workspace_version() -> anyhow::Result<bool> {
+    let manifest: DocumentMut = std::fs::read_to_string(root_manifest_path())?.parse()?;
+    if get_workspace_version(&manifest).is_none() {
+        return Ok(false);
+    }
+    let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+    members.extend(get_workspace_members(WorkspaceMemberType::Example));
+    for member in members {
+        let path = Path::new(&member.path).join("Cargo.toml");
+        let member_manifest: DocumentMut = std::fs::read_to_string(&path)?.parse()?;
+        if inherits_workspace_version(&member_manifest) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn get_workspace_version(manifest: &DocumentMut) -> Option<&str> {
+    manifest
+        .get("workspace")?
+        .get("package")?
+        .get("version")?
+        .as_str()
+}
+
+fn inherits_workspace_version(manifest: &DocumentMut) -> bool {
+    manifest
+        .get("package")
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.get("workspace"))
+        .and_then(|w| w.as_bool())
+        .unwrap_or(false)
+}
+
+/// Bump the `[workspace.package]` version of MANIFEST and return the updated manifest along
+/// with the new version. Formatting and comments of the manifest are preserved.
+fn bump_workspace_version_in(
+    manifest: &str,
+    command: &BumpSubCommand,
+    metadata: Option<&BuildMetadata>,
+) -> anyhow::Result<(String, Version)> {
+    let mut manifest: DocumentMut = manifest.parse()?;
+    let current = get_workspace_version(&manifest).ok_or_else(|| {
+        anyhow::anyhow!("No [workspace.package] version found in the root manifest")
+    })?;
+    let version = bump_version(&Version::parse(current)?, command, metadata)?;
+    manifest["workspace"]["package"]["version"] = toml_edit::value(version.to_string());
+    Ok((manifest.to_string(), version))
+}
+
+/// Return the CURRENT version bumped with COMMAND and tagged with the build METADATA.
+/// Like 'cargo set-version', a patch bump of a prerelease releases it (1.2.4-rc.1 -> 1.2.4).
+fn bump_version(
+    current: &Version,
+    command: &BumpSubCommand,
+    metadata: Option<&BuildMetadata>,
+) -> anyhow::Result<Version> {
+    let mut version = match command {
+        BumpSubCommand::Major => Version::new(current.major + 1, 0, 0),
+        BumpSubCommand::Minor => Version::new(current.major, current.minor + 1, 0),
+        BumpSubCommand::Patch if !current.pre.is_empty() => {
+            Version::new(current.major, current.minor, current.patch)
+        }
+        BumpSubCommand::Patch => Version::new(current.major, current.minor, current.patch + 1),
+        BumpSubCommand::Prerelease(args) => bump_prerelease(current, &args.identifier)?,
+    };
+    if let Some(metadata) = metadata {
+        version.build = metadata.clone();
+    }
+    Ok(version)
+}
+
+/// Bump CURRENT to the next prerelease with the given IDENTIFIER:
+/// - a release starts the first prerelease of the next patch version: 1.2.3 -> 1.2.4-rc.1
+/// - a prerelease with the same identifier is incremented: 1.2.4-rc.1 -> 1.2.4-rc.2
+/// - a prerelease with another identifier starts over: 1.2.4-beta.2 -> 1.2.4-rc.1,
+///   as long as the new prerelease has a higher precedence.
+fn bump_prerelease(current: &Version, identifier: &str) -> anyhow::Result<Version> {
+    if identifier.is_empty()
+        || identifier.contains('.')
+        || identifier.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid prerelease identifier '{}', expected a single non-numeric identifier like 'rc'.",
+            identifier
+        ));
+    }
+    let prerelease = |number: u64| -> anyhow::Result<Prerelease> {
+        Prerelease::new(&format!("{identifier}.{number}"))
+            .map_err(|e| anyhow::anyhow!("Invalid prerelease identifier '{}': {}", identifier, e))
+    };
+    let mut version = Version::new(current.major, current.minor, current.patch);
+    if current.pre.is_empty() {
+        version.patch += 1;
+        version.pre = prerelease(1)?;
+        return Ok(version);
+    }
+    let parts: Vec<&str> = current.pre.split('.').collect();
+    version.pre = match parts.as_slice() {
+        [id] if *id == identifier => prerelease(1)?,
+        [id, number] if *id == identifier => match number.parse::<u64>() {
+            Result::Ok(number) => prerelease(number + 1)?,
+            Err(_) => {
+                return Err(anyhow::anyhow!(
+                    "Cannot increment the prerelease '{}' of {}.",
+                    current.pre,
+                    current
+                ))
+            }
+        },
+        [id, ..] if *id == identifier => {
+            return Err(anyhow::anyhow!(
+                "Cannot increment the prerelease '{}' of {}.",
+                current.pre,
+                current
+            ))
+        }
+        _ => prerelease(1)?,
+    };
+    if version <= *current {
+        return Err(anyhow::anyhow!(
+            "Bumping {} to {} would lower the version, use an identifier with a higher precedence.",
+            current,
+            version
+        ));
+    }
+    Ok(version)
+}
+
+/// Version change of a crate proposed by '--dry-run'.
+struct VersionChange {
+    name: String,
+    current: Version,
+    proposed: Version,
+}
+
+/// Version changes that a bump would apply, see '--dry-run'.
+struct BumpPlan(Vec<VersionChange>);
+
+impl CommandResult for BumpPlan {
+    fn to_human(&self) -> String {
+        let header = ("Crate", "Current", "Proposed");
+        let rows: Vec<(&str, String, String)> = self
+            .0
+            .iter()
+            .map(|c| {
+                (
+                    c.name.as_str(),
+                    c.current.to_string(),
+                    c.proposed.to_string(),
+                )
+            })
+            .collect();
+        let name_width = rows
+            .iter()
+            .map(|r| r.0.len())
+            .chain([header.0.len()])
+            .max()
+            .unwrap_or_default();
+        let current_width = rows
+            .iter()
+            .map(|r| r.1.len())
+            .chain([header.1.len()])
+            .max()
+            .unwrap_or_default();
+        let mut lines = vec![format!(
+            "{:<name_width$}  {:<current_width$}  {}",
+            header.0, header.1, header.2
+        )];
+        for (name, current, proposed) in rows {
+            lines.push(format!(
+                "{:<name_width$}  {:<current_width$}  {}",
+                name, current, proposed
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "name": c.name,
+                    "current": c.current.to_string(),
+                    "proposed": c.proposed.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Return the version changes of the workspace members for the given bump COMMAND.
+/// With WORKSPACE_VERSION only the members inheriting the workspace version change.
+fn bump_plan(
+    command: &BumpSubCommand,
+    metadata: Option<&BuildMetadata>,
+    workspace_version: bool,
+) -> anyhow::Result<BumpPlan> {
+    let root: DocumentMut = std::fs::read_to_string(root_manifest_path())?.parse()?;
+    let inherited = get_workspace_version(&root)
+        .map(Version::parse)
+        .transpose()?;
+    let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+    members.extend(get_workspace_members(WorkspaceMemberType::Example));
+    let mut changes = vec![];
+    for member in members {
+        let path = Path::new(&member.path).join("Cargo.toml");
+        let manifest: DocumentMut = std::fs::read_to_string(&path)?.parse()?;
+        if let Some(current) = current_version(&manifest, inherited.as_ref(), workspace_version)? {
+            changes.push(VersionChange {
+                name: member.name,
+                proposed: bump_version(&current, command, metadata)?,
+                current,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(BumpPlan(changes))
+}
+
+/// Return the version of the package MANIFEST that a bump would change, if any.
+/// The INHERITED workspace version is used when the package declares 'version.workspace = true'
+/// and only these packages change in WORKSPACE_VERSION mode.
+fn current_version(
+    manifest: &DocumentMut,
+    inherited: Option<&Version>,
+    workspace_version: bool,
+) -> anyhow::Result<Option<Version>> {
+    if inherits_workspace_version(manifest) {
+        return Ok(inherited.cloned());
+    }
+    if workspace_version {
+        return Ok(None);
+    }
+    manifest
+        .get("package")
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.as_str())
+        .map(Version::parse)
+        .transpose()
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const ROOT_FIXTURE: &str = r#"[workspace]
+members = ["crates/*"]
+
+[workspace.package]
+edition = "2021"
+# bumped by 'cargo xtask bump'
+version = "1.4.2"
+"#;
+
+    #[rstest]
+    #[case::major(BumpSubCommand::Major, "2.0.0")]
+    #[case::minor(BumpSubCommand::Minor, "1.5.0")]
+    #[case::patch(BumpSubCommand::Patch, "1.4.3")]
+    fn test_bump_workspace_version_in(#[case] command: BumpSubCommand, #[case] expected: &str) {
+        let (manifest, version) = bump_workspace_version_in(ROOT_FIXTURE, &command, None).unwrap();
+        assert_eq!(version.to_string(), expected);
+        assert_eq!(
+            manifest,
+            ROOT_FIXTURE.replace("\"1.4.2\"", &format!("\"{expected}\""))
+        );
+    }
+
+    #[rstest]
+    #[case::inherited("[package]\nname = \"a\"\nversion.workspace = true\n", true)]
+    #[case::inline_table("[package]\nname = \"a\"\nversion = { workspace = true }\n", true)]
+    #[case::own_version("[package]\nname = \"a\"\nversion = \"0.1.0\"\n", false)]
+    fn test_inherits_workspace_version(#[case] manifest: &str, #[case] expected: bool) {
+        let manifest: DocumentMut = manifest.parse().unwrap();
+        assert_eq!(inherits_workspace_version(&manifest), expected);
+    }
+
+    #[rstest]
+    #[case::own_version("[package]\nversion = \"0.1.0\"\n", false, Some("0.1.0"))]
+    #[case::own_version_workspace_mode("[package]\nversion = \"0.1.0\"\n", true, None)]
+    #[case::inherited("[package]\nversion.workspace = true\n", false, Some("1.4.2"))]
+    #[case::inherited_workspace_mode("[package]\nversion.workspace = true\n", true, Some("1.4.2"))]
+    #[case::no_version("[package]\nname = \"a\"\n", false, None)]
+    fn test_current_version(
+        #[case] manifest: &str,
+        #[case] workspace_version: bool,
+        #[case] expected: Option<&str>,
+    ) {
+        let manifest: DocumentMut = manifest.parse().unwrap();
+        let inherited = Version::new(1, 4, 2);
+        let version = current_version(&manifest, Some(&inherited), workspace_version).unwrap();
+        assert_eq!(version.map(|v| v.to_string()).as_deref(), expected);
+    }
+
+    fn prerelease(identifier: &str) -> BumpSubCommand {
+        BumpSubCommand::Prerelease(PrereleaseCmdArgs {
+            identifier: identifier.to_string(),
+        })
+    }
+
+    #[rstest]
+    #[case::major_of_release("1.2.3", BumpSubCommand::Major, "2.0.0")]
+    #[case::major_of_prerelease("1.2.3-rc.1", BumpSubCommand::Major, "2.0.0")]
+    #[case::minor_of_prerelease("1.2.3-rc.1", BumpSubCommand::Minor, "1.3.0")]
+    #[case::patch_of_release("1.2.3", BumpSubCommand::Patch, "1.2.4")]
+    #[case::patch_releases_prerelease("1.2.4-rc.2", BumpSubCommand::Patch, "1.2.4")]
+    #[case::patch_drops_metadata("1.2.3+abc", BumpSubCommand::Patch, "1.2.4")]
+    #[case::release_to_prerelease("1.2.3", prerelease("rc"), "1.2.4-rc.1")]
+    #[case::next_prerelease("1.2.4-rc.1", prerelease("rc"), "1.2.4-rc.2")]
+    #[case::next_prerelease_multiple_digits("1.2.4-rc.9", prerelease("rc"), "1.2.4-rc.10")]
+    #[case::numbered_prerelease("1.2.4-rc", prerelease("rc"), "1.2.4-rc.1")]
+    #[case::higher_prerelease("1.2.4-beta.2", prerelease("rc"), "1.2.4-rc.1")]
+    fn test_bump_version(
+        #[case] current: &str,
+        #[case] command: BumpSubCommand,
+        #[case] expected: &str,
+    ) {
+        let current = Version::parse(current).unwrap();
+        let version = bump_version(&current, &command, None).unwrap();
+        assert_eq!(version.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case::lower_prerelease("1.2.4-rc.1", "beta")]
+    #[case::not_numbered_prerelease("1.2.4-rc.final", "rc")]
+    #[case::empty_identifier("1.2.3", "")]
+    #[case::numeric_identifier("1.2.3", "1")]
+    #[case::dotted_identifier("1.2.3", "rc.1")]
+    #[case::invalid_identifier("1.2.3", "r_c")]
+    fn test_bump_prerelease_errors(#[case] current: &str, #[case] identifier: &str) {
+        let current = Version::parse(current).unwrap();
+        assert!(bump_version(&current, &prerelease(identifier), None).is_err());
+    }
+
+    #[rstest]
+    #[case::release(BumpSubCommand::Minor, "1.5.0+build.7")]
+    #[case::prerelease(prerelease("rc"), "1.4.3-rc.1+build.7")]
+    fn test_bump_version_with_metadata(#[case] command: BumpSubCommand, #[case] expected: &str) {
+        let current = Version::parse("1.4.2").unwrap();
+        let metadata = BuildMetadata::new("build.7").unwrap();
+        let version = bump_version(&current, &command, Some(&metadata)).unwrap();
+        assert_eq!(version.to_string(), expected);
+    }
+
+    #[test]
+    fn test_bump_plan_to_human() {
+        let plan = BumpPlan(vec![
+            VersionChange {
+                name: "tracel-xtask".to_string(),
+                current: Version::new(1, 4, 2),
+                proposed: Version::new(1, 5, 0),
+            },
+            VersionChange {
+                name: "xtask".to_string(),
+                current: Version::new(10, 0, 0),
+                proposed: Version::new(10, 1, 0),
+            },
+        ]);
+        assert_eq!(
+            plan.to_human(),
+            "Crate         Current  Proposed\n\
+             tracel-xtask  1.4.2    1.5.0\n\
+             xtask         10.0.0   10.1.0"
+        );
+    }
+}
@@ -1,28 +1,76 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
 use anyhow::Ok;
+use serde_json::Value;
 use strum::IntoEnumIterator;
+use toml_edit::DocumentMut;
 
 use crate::{
-    commands::WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS,
-    endgroup, group,
+    classify_error,
+    commands::{
+        ensure_hack_is_installed, typos_config_args, with_hack_mode, HackMode, CARGO_NIGHTLY_MSG,
+        WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS,
+    },
+    endgroup,
+    errors::{process_failed, spawn_error},
+    group, group_info,
     utils::{
         cargo::ensure_cargo_crate_is_installed,
-        process::{run_process, run_process_for_package, run_process_for_workspace},
-        workspace::{get_workspace_members, WorkspaceMemberType},
+        ci::is_ci,
+        git::{changed_packages_since, display_relative_to_root},
+        process::{
+            run_process, run_process_for_package, run_process_for_workspace, with_cargo_extra_args,
+            CargoLockArgs,
+        },
+        rustup::is_current_toolchain_nightly,
+        tool::ensure_tool_available,
+        workspace::{
+            get_package_names_by_id, get_publishable_workspace_members, get_workspace_members,
+            get_workspace_root, WorkspaceMemberType,
+        },
     },
     versions::TYPOS_VERSION,
+    XtaskError,
 };
 
-use super::Target;
+use super::{build::run_with_compact_diagnostics, Target};
 
 #[tracel_xtask_macros::declare_command_args(Target, CheckSubCommand)]
 pub struct CheckCmdArgs {}
 
-pub fn handle_command(args: CheckCmdArgs) -> anyhow::Result<()> {
+pub fn handle_command(mut args: CheckCmdArgs) -> anyhow::Result<()> {
+    // only the clippy invocations of the lint check accept the lockfile and network flags
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    if let Some(reference) = &args.changed_since {
+        if matches!(
+            args.get_command(),
+            CheckSubCommand::Format | CheckSubCommand::Lint | CheckSubCommand::ManifestLint
+        ) {
+            let changed: Vec<String> = changed_packages_since(reference)?
+                .into_iter()
+                .filter(|p| args.only.is_empty() || args.only.contains(p))
+                .collect();
+            if changed.is_empty() {
+                info!(
+                    "No package changed since '{}', skipping '{}' check.",
+                    reference,
+                    args.get_command()
+                );
+                return Ok(());
+            }
+            args.target = Target::AllPackages;
+            args.only = changed;
+        }
+    }
     if args.target == Target::Workspace && (!args.exclude.is_empty() || !args.only.is_empty()) {
         warn!("{}", WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS);
     }
 
-    match args.get_command() {
+    let result = match args.get_command() {
         CheckSubCommand::Audit if args.ignore_audit => {
             if run_audit().is_err() {
                 warn!("Ignoring audit error because of '--ignore-audit' flag.");
@@ -31,20 +79,104 @@ pub fn handle_command(args: CheckCmdArgs) -> anyhow::Result<()> {
         }
         CheckSubCommand::Audit => run_audit(),
         CheckSubCommand::Format => run_format(&args.target, &args.exclude, &args.only),
-        CheckSubCommand::Lint => run_lint(&args.target, &args.exclude, &args.only),
-        CheckSubCommand::Typos => run_typos(),
-        CheckSubCommand::All => CheckSubCommand::iter()
-            .filter(|c| *c != CheckSubCommand::All)
-            .try_for_each(|c| {
-                handle_command(CheckCmdArgs {
-                    command: Some(c),
+        CheckSubCommand::Lint => match &args.baseline {
+            Some(path) => run_lint_baseline(
+                path,
+                args.check_baseline,
+                &args.target,
+                &args.exclude,
+                &args.only,
+                args.all_features,
+                lock_args,
+            ),
+            None if args.compact_diagnostics => {
+                let mut cmd_args = vec!["clippy", "--workspace", "--no-deps"];
+                if args.all_features {
+                    cmd_args.push("--all-features");
+                }
+                cmd_args.extend(["--", "--deny", "warnings"]);
+                run_with_compact_diagnostics(
+                    &cmd_args,
+                    &args.exclude,
+                    lock_args,
+                    "Workspace lint failed",
+                )
+            }
+            None => {
+                ensure_hack_is_installed(args.hack)?;
+                run_lint(
+                    &args.target,
+                    &args.exclude,
+                    &args.only,
+                    args.all_features,
+                    args.hack,
+                    lock_args,
+                )
+            }
+        },
+        CheckSubCommand::LockCheck => run_lock_check(),
+        CheckSubCommand::ManifestLint => run_manifest_lint(&args.exclude, &args.only, args.strict),
+        CheckSubCommand::MinimalVersions => run_minimal_versions(&args.exclude),
+        CheckSubCommand::Msrv => run_msrv(&args.target, &args.exclude, &args.only),
+        CheckSubCommand::PubCheck => match &args.api_baseline {
+            Some(dir) => run_pub_check(dir, args.check_api_baseline, &args.exclude, &args.only),
+            None => Err(anyhow::anyhow!(
+                "The 'pub-check' check requires an '--api-baseline' directory."
+            )),
+        },
+        CheckSubCommand::SemverCheck => run_semver_check(&args.exclude, &args.only),
+        CheckSubCommand::Typos => run_typos(args.typos_config.as_deref()),
+        // the public API and semver checks need a baseline (committed or published), the lock
+        // check needs a committed Cargo.lock, the minimal versions check needs a nightly
+        // toolchain and the MSRV check installs older toolchains so they are only executed
+        // explicitly
+        CheckSubCommand::All => {
+            let mut failed = vec![];
+            for c in CheckSubCommand::iter().filter(|c| {
+                *c != CheckSubCommand::All
+                    && *c != CheckSubCommand::LockCheck
+                    && *c != CheckSubCommand::MinimalVersions
+                    && *c != CheckSubCommand::Msrv
+                    && *c != CheckSubCommand::PubCheck
+                    && *c != CheckSubCommand::SemverCheck
+            }) {
+                let result = handle_command(CheckCmdArgs {
+                    command: Some(c.clone()),
                     target: args.target.clone(),
                     exclude: args.exclude.clone(),
                     only: args.only.clone(),
+                    locked: args.locked,
+                    frozen: args.frozen,
+                    offline: args.offline,
                     ignore_audit: args.ignore_audit,
-                })
-            }),
-    }
+                    baseline: args.baseline.clone(),
+                    check_baseline: args.check_baseline,
+                    api_baseline: args.api_baseline.clone(),
+                    check_api_baseline: args.check_api_baseline,
+                    compact_diagnostics: args.compact_diagnostics,
+                    strict: args.strict,
+                    all_features: args.all_features,
+                    changed_since: args.changed_since.clone(),
+                    keep_going: args.keep_going,
+                    hack: args.hack,
+                    typos_config: args.typos_config.clone(),
+                });
+                match result {
+                    Err(e) if args.keep_going => {
+                        error!("Check '{}' failed: {}", c, e);
+                        failed.push(c.to_string());
+                    }
+                    result => result?,
+                }
+            }
+            if failed.is_empty() {
+                Ok(())
+            } else {
+                Err(XtaskError::CheckFailed(format!("Failed checks: {}", failed.join(", "))).into())
+            }
+        }
+    };
+    classify_error(result, XtaskError::CheckFailed, "Checks failed")
 }
 
 fn run_audit() -> anyhow::Result<()> {
@@ -68,6 +200,7 @@ fn run_format(target: &Target, excluded: &[String], only: &[String]) -> anyhow::
             run_process_for_workspace(
                 "cargo",
                 &["fmt", "--check"],
+                None,
                 &[],
                 None,
                 None,
@@ -90,6 +223,7 @@ fn run_format(target: &Target, excluded: &[String], only: &[String]) -> anyhow::
                     "cargo",
                     &member.name,
                     &["fmt", "--check", "-p", &member.name],
+                    None,
                     excluded,
                     only,
                     &format!("Format check execution failed for {}", &member.name),
@@ -108,20 +242,26 @@ fn run_format(target: &Target, excluded: &[String], only: &[String]) -> anyhow::
     Ok(())
 }
 
-fn run_lint(target: &Target, excluded: &[String], only: &[String]) -> anyhow::Result<()> {
+fn run_lint(
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+    all_features: bool,
+    hack: Option<HackMode>,
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Lint Workspace");
+            let mut cmd_args = vec!["clippy", "--no-deps", "--color=always"];
+            if all_features {
+                cmd_args.push("--all-features");
+            }
+            cmd_args.extend(["--", "--deny", "warnings"]);
             run_process_for_workspace(
                 "cargo",
-                &[
-                    "clippy",
-                    "--no-deps",
-                    "--color=always",
-                    "--",
-                    "--deny",
-                    "warnings",
-                ],
+                &with_hack_mode(&lock_args.apply(&cmd_args), hack),
+                None,
                 &[],
                 None,
                 None,
@@ -140,19 +280,17 @@ fn run_lint(target: &Target, excluded: &[String], only: &[String]) -> anyhow::Re
 
             for member in members {
                 group!("Lint: {}", member.name);
+                let mut cmd_args =
+                    vec!["clippy", "--no-deps", "--color=always", "-p", &member.name];
+                if all_features {
+                    cmd_args.push("--all-features");
+                }
+                cmd_args.extend(["--", "--deny", "warnings"]);
                 run_process_for_package(
                     "cargo",
                     &member.name,
-                    &[
-                        "clippy",
-                        "--no-deps",
-                        "--color=always",
-                        "-p",
-                        &member.name,
-                        "--",
-                        "--deny",
-                        "warnings",
-                    ],
+                    &with_hack_mode(&lock_args.apply(&cmd_args), hack),
+                    None,
                     excluded,
                     only,
                     &format!("Lint fix execution failed for {}", &member.name),
@@ -165,24 +303,629 @@ fn run_lint(target: &Target, excluded: &[String], only: &[String]) -> anyhow::Re
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_lint(&t, excluded, only))?;
+                .try_for_each(|t| run_lint(&t, excluded, only, all_features, hack, lock_args))?;
         }
     }
     Ok(())
 }
 
-fn run_typos() -> anyhow::Result<()> {
-    if std::env::var("CI").is_err() {
-        ensure_cargo_crate_is_installed("typos-cli", None, Some(TYPOS_VERSION), false)?;
+/// Write the clippy warning counts per crate to the baseline file or, if `check` is true,
+/// fail if any crate has more warnings than recorded in the baseline file.
+/// For targets other than the workspace only the selected members are linted and the
+/// baseline of the other members is kept when writing the file.
+fn run_lint_baseline(
+    path: &Path,
+    check: bool,
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+    all_features: bool,
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    group!("Lint Baseline");
+    let members = match target {
+        Target::Workspace => None,
+        _ => Some(lint_baseline_members(target, excluded, only)),
+    };
+    let mut args = vec!["clippy", "--no-deps", "--message-format=json"];
+    match &members {
+        None => {
+            args.push("--workspace");
+            excluded
+                .iter()
+                .for_each(|e| args.extend(["--exclude", e.as_str()]));
+        }
+        Some(members) if members.is_empty() => {
+            warn!("No member selected, the baseline has not been linted.");
+            endgroup!();
+            return Ok(());
+        }
+        Some(members) => members.iter().for_each(|m| args.extend(["-p", m.as_str()])),
     }
-    group!("Typos");
+    if all_features {
+        args.push("--all-features");
+    }
+    let args = with_cargo_extra_args(&lock_args.apply(&args));
+    info!("Command line: cargo {}", args.join(" "));
+    let output = Command::new("cargo")
+        .args(&args)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo clippy"))?;
+    if !output.status.success() {
+        return Err(process_failed("Clippy execution failed"));
+    }
+    let current = count_warnings(
+        &String::from_utf8_lossy(&output.stdout),
+        &get_package_names_by_id(),
+    );
+    if check {
+        let baseline = read_lint_baseline(path)?;
+        let regressions = compare_with_baseline(&current, &baseline);
+        if !regressions.is_empty() {
+            regressions.iter().for_each(|r| error!("{}", r));
+            return Err(XtaskError::CheckFailed(format!(
+                "Clippy warnings exceed the baseline {}",
+                path.display()
+            ))
+            .into());
+        }
+        info!(
+            "No new clippy warnings compared to {}",
+            display_relative_to_root(path)
+        );
+    } else {
+        let baseline = match &members {
+            Some(members) if path.exists() => {
+                let mut baseline = read_lint_baseline(path)?;
+                baseline.retain(|name, _| !members.contains(name));
+                baseline.extend(current);
+                baseline
+            }
+            _ => current,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&baseline)? + "\n")?;
+        info!(
+            "Clippy warnings baseline written to {}",
+            display_relative_to_root(path)
+        );
+    }
+    endgroup!();
+    Ok(())
+}
+
+/// Return the names of the members of TARGET which are neither excluded nor filtered out by ONLY.
+fn lint_baseline_members(target: &Target, excluded: &[String], only: &[String]) -> Vec<String> {
+    let members = match target {
+        Target::Crates => get_workspace_members(WorkspaceMemberType::Crate),
+        Target::Examples => get_workspace_members(WorkspaceMemberType::Example),
+        _ => get_workspace_members(WorkspaceMemberType::Crate)
+            .into_iter()
+            .chain(get_workspace_members(WorkspaceMemberType::Example))
+            .collect(),
+    };
+    members
+        .into_iter()
+        .map(|m| m.name)
+        .filter(|name| !excluded.contains(name) && (only.is_empty() || only.contains(name)))
+        .collect()
+}
+
+fn read_lint_baseline(path: &Path) -> anyhow::Result<BTreeMap<String, usize>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read baseline file {}: {}", path.display(), e))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Count the warnings per crate in the JSON messages emitted by cargo.
+fn count_warnings(
+    messages: &str,
+    package_names: &HashMap<String, String>,
+) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for message in messages
+        .lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .filter(|m| m["reason"] == "compiler-message" && m["message"]["level"] == "warning")
+    {
+        // summaries like 'N warnings emitted' are not attached to any source span
+        if message["message"]["spans"]
+            .as_array()
+            .map_or(true, |spans| spans.is_empty())
+        {
+            continue;
+        }
+        if let Some(id) = message["package_id"].as_str() {
+            let name = package_names.get(id).cloned().unwrap_or(id.to_string());
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Return a description of each crate having more warnings than in the baseline.
+fn compare_with_baseline(
+    current: &BTreeMap<String, usize>,
+    baseline: &BTreeMap<String, usize>,
+) -> Vec<String> {
+    current
+        .iter()
+        .filter_map(|(name, count)| {
+            let allowed = baseline.get(name).copied().unwrap_or(0);
+            (*count > allowed)
+                .then(|| format!("{name}: {count} clippy warnings (baseline allows {allowed})"))
+        })
+        .collect()
+}
+
+/// Report the common manifest issues of the workspace members, they are errors in STRICT mode.
+fn run_manifest_lint(excluded: &[String], only: &[String], strict: bool) -> anyhow::Result<()> {
+    group!("Manifest Lint");
+    let publishable = get_publishable_workspace_members();
+    let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+    members.extend(get_workspace_members(WorkspaceMemberType::Example));
+    let mut issues_count = 0;
+    for member in members {
+        if excluded.contains(&member.name) || (!only.is_empty() && !only.contains(&member.name)) {
+            continue;
+        }
+        let path = Path::new(&member.path).join("Cargo.toml");
+        let manifest: DocumentMut = std::fs::read_to_string(&path)?.parse()?;
+        for issue in lint_manifest(&manifest, publishable.contains(&member.name)) {
+            if strict {
+                error!("{}: {}", display_relative_to_root(&path), issue);
+            } else {
+                warn!("{}: {}", display_relative_to_root(&path), issue);
+            }
+            issues_count += 1;
+        }
+    }
+    if strict && issues_count > 0 {
+        return Err(
+            XtaskError::CheckFailed(format!("Found {issues_count} manifest issue(s).")).into(),
+        );
+    }
+    info!("Found {issues_count} manifest issue(s).");
+    endgroup!();
+    Ok(())
+}
+
+/// Return the issues found in MANIFEST, some of them only block the publication of PUBLISHABLE crates.
+fn lint_manifest(manifest: &DocumentMut, publishable: bool) -> Vec<String> {
+    let mut issues = vec![];
+    if publishable {
+        let package = manifest.get("package");
+        let has_field = |field: &str| package.and_then(|p| p.get(field)).is_some();
+        for field in ["description", "license", "repository"] {
+            let present = has_field(field) || (field == "license" && has_field("license-file"));
+            if !present {
+                issues.push(format!("missing '{field}' field required to publish"));
+            }
+        }
+    }
+    // dependencies can be declared for all targets or for a specific one
+    let mut tables = vec![];
+    for kind in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        if let Some(table) = manifest.get(kind).and_then(|t| t.as_table_like()) {
+            tables.push((kind.to_string(), table));
+        }
+        if let Some(targets) = manifest.get("target").and_then(|t| t.as_table_like()) {
+            for (target, item) in targets.iter() {
+                if let Some(table) = item.get(kind).and_then(|t| t.as_table_like()) {
+                    tables.push((format!("target.{target}.{kind}"), table));
+                }
+            }
+        }
+    }
+    for (kind, table) in tables {
+        for (name, dependency) in table.iter() {
+            let version = match dependency.as_str() {
+                Some(version) => Some(version),
+                None => dependency.get("version").and_then(|v| v.as_str()),
+            };
+            if version.is_some_and(|v| v.trim() == "*") {
+                issues.push(format!("{kind}: '{name}' uses the wildcard version '*'"));
+            }
+            // path only dev-dependencies are stripped by cargo when publishing
+            let is_path_only = dependency.get("path").is_some() && version.is_none();
+            if publishable && is_path_only && !kind.ends_with("dev-dependencies") {
+                issues.push(format!(
+                    "{kind}: path dependency '{name}' has no version required to publish"
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Write the public API of each publishable crate to DIR or, if `check` is true, fail if a
+/// public API has items which are not in the baseline of DIR.
+fn run_pub_check(
+    dir: &Path,
+    check: bool,
+    excluded: &[String],
+    only: &[String],
+) -> anyhow::Result<()> {
+    ensure_cargo_crate_is_installed("cargo-public-api", None, None, true)?;
+    let mut failed = false;
+    for member in get_publishable_workspace_members() {
+        if excluded.contains(&member) || (!only.is_empty() && !only.contains(&member)) {
+            group_info!("Skip '{}' because it has been excluded!", member);
+            continue;
+        }
+        group!("Public API: {}", member);
+        // cargo-public-api builds the rustdoc JSON with a nightly toolchain by itself
+        let output = Command::new("cargo")
+            .args(["public-api", "--simplified", "-p", &member])
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo public-api"))?;
+        if !output.status.success() {
+            return Err(process_failed(&format!(
+                "Cannot list the public API of {}",
+                member
+            )));
+        }
+        let current = String::from_utf8_lossy(&output.stdout);
+        let path = dir.join(format!("{member}.txt"));
+        if check {
+            let baseline = std::fs::read_to_string(&path).map_err(|e| {
+                anyhow::anyhow!("Failed to read baseline file {}: {}", path.display(), e)
+            })?;
+            let additions = public_api_additions(&current, &baseline);
+            if additions.is_empty() {
+                info!(
+                    "No new public items compared to {}",
+                    display_relative_to_root(&path)
+                );
+            } else {
+                additions
+                    .iter()
+                    .for_each(|a| error!("New public item: {}", a));
+                failed = true;
+            }
+        } else {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(&path, current.as_bytes())?;
+            info!(
+                "Public API baseline written to {}",
+                display_relative_to_root(&path)
+            );
+        }
+        endgroup!();
+    }
+    if failed {
+        return Err(XtaskError::CheckFailed(format!(
+            "Public API has new items compared to the baseline in {}",
+            dir.display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Fail if cargo would need to update Cargo.lock to match the manifests of the workspace.
+fn run_lock_check() -> anyhow::Result<()> {
+    group!("Lock Check");
+    let path = get_workspace_root().join("Cargo.lock");
+    let original = std::fs::read(&path).map_err(|e| {
+        anyhow::anyhow!(
+            "Cannot read {}, the lock check requires a committed Cargo.lock: {}",
+            path.display(),
+            e
+        )
+    })?;
     run_process(
-        "typos",
-        &["--diff", "--color", "always"],
+        "cargo",
+        &["update", "--workspace", "--locked"],
+        None,
+        None,
+        "Cargo.lock is not up to date, run 'cargo update --workspace' and commit the result",
+    )?;
+    // '--locked' prevents any write but make sure nothing changed
+    if std::fs::read(&path)? != original {
+        std::fs::write(&path, &original)?;
+        return Err(XtaskError::CheckFailed(
+            "Cargo.lock is not up to date, run 'cargo update --workspace' and commit the result"
+                .to_string(),
+        )
+        .into());
+    }
+    endgroup!();
+    Ok(())
+}
+
+/// Restore the content of Cargo.lock as it was when the guard was created, or remove the
+/// file if it did not exist, when the guard is dropped.
+struct LockfileGuard {
+    path: PathBuf,
+    original: Option<Vec<u8>>,
+}
+
+impl LockfileGuard {
+    fn new(path: PathBuf) -> Self {
+        let original = std::fs::read(&path).ok();
+        Self { path, original }
+    }
+}
+
+impl Drop for LockfileGuard {
+    fn drop(&mut self) {
+        let result = match &self.original {
+            Some(content) => std::fs::write(&self.path, content),
+            None => std::fs::remove_file(&self.path),
+        };
+        if let Err(e) = result {
+            error!("Failed to restore {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn run_minimal_versions(excluded: &[String]) -> anyhow::Result<()> {
+    if !is_current_toolchain_nightly() {
+        return Err(anyhow::anyhow!("{}", CARGO_NIGHTLY_MSG));
+    }
+    group!("Minimal Versions");
+    // the lockfile is restored even if the resolution or the build fails
+    let _guard = LockfileGuard::new(get_workspace_root().join("Cargo.lock"));
+    run_process(
+        "cargo",
+        &["update", "-Z", "minimal-versions"],
+        None,
+        None,
+        "Failed to resolve the minimal versions of the dependencies",
+    )?;
+    run_process_for_workspace(
+        "cargo",
+        &["build", "--workspace", "--color", "always"],
+        None,
+        excluded,
+        None,
+        None,
+        "Workspace build failed with the minimal versions of the dependencies",
         None,
         None,
-        "Typos check execution failed",
     )?;
     endgroup!();
     Ok(())
 }
+
+/// Verify with cargo-msrv that the packages of the target build with the minimum supported Rust
+/// version declared in their manifest. Packages without 'rust-version' are skipped.
+fn run_msrv(target: &Target, excluded: &[String], only: &[String]) -> anyhow::Result<()> {
+    let members = match target {
+        Target::Crates => get_workspace_members(WorkspaceMemberType::Crate),
+        Target::Examples => get_workspace_members(WorkspaceMemberType::Example),
+        Target::AllPackages | Target::Workspace => {
+            let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+            members.extend(get_workspace_members(WorkspaceMemberType::Example));
+            members
+        }
+    };
+    ensure_cargo_crate_is_installed("cargo-msrv", None, None, true)?;
+    let workspace_manifest: DocumentMut =
+        std::fs::read_to_string(get_workspace_root().join("Cargo.toml"))?.parse()?;
+    let mut failed = vec![];
+    for member in members {
+        if excluded.contains(&member.name) || (!only.is_empty() && !only.contains(&member.name)) {
+            group_info!("Skip '{}' because it has been excluded!", &member.name);
+            continue;
+        }
+        let path = Path::new(&member.path);
+        let manifest: DocumentMut = std::fs::read_to_string(path.join("Cargo.toml"))?.parse()?;
+        let Some(rust_version) = manifest_rust_version(&manifest, &workspace_manifest) else {
+            info!(
+                "Skip '{}' because it does not declare a 'rust-version'.",
+                &member.name
+            );
+            continue;
+        };
+        group!("MSRV {}: {}", rust_version, member.name);
+        if let Err(e) = run_process(
+            "cargo",
+            &["msrv", "verify"],
+            None,
+            Some(path),
+            &format!(
+                "{} does not build with its MSRV {}",
+                member.name, rust_version
+            ),
+        ) {
+            error!("{}", e);
+            failed.push(format!("{} ({})", member.name, rust_version));
+        }
+        endgroup!();
+    }
+    if !failed.is_empty() {
+        return Err(XtaskError::CheckFailed(format!(
+            "Crates failing their declared MSRV: {}",
+            failed.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Return the 'rust-version' of the package MANIFEST, inherited from the WORKSPACE manifest
+/// when it is declared with 'rust-version.workspace = true'.
+fn manifest_rust_version(manifest: &DocumentMut, workspace: &DocumentMut) -> Option<String> {
+    let rust_version = manifest.get("package")?.get("rust-version")?;
+    let inherited = rust_version
+        .get("workspace")
+        .and_then(|w| w.as_bool())
+        .unwrap_or(false);
+    let rust_version = if inherited {
+        workspace
+            .get("workspace")?
+            .get("package")?
+            .get("rust-version")?
+    } else {
+        rust_version
+    };
+    rust_version.as_str().map(str::to_string)
+}
+
+/// Check the publishable crates against their latest published version for semver breaking
+/// changes which are not accompanied by the corresponding version bump.
+fn run_semver_check(excluded: &[String], only: &[String]) -> anyhow::Result<()> {
+    ensure_cargo_crate_is_installed("cargo-semver-checks", None, None, true)?;
+    for member in get_publishable_workspace_members() {
+        group!("Semver Check: {}", member);
+        run_process_for_package(
+            "cargo",
+            &member,
+            &["semver-checks", "check-release", "-p", &member],
+            None,
+            excluded,
+            only,
+            &format!("Semver check found breaking changes in {}", &member),
+            None,
+            None,
+        )?;
+        endgroup!();
+    }
+    Ok(())
+}
+
+/// Return the items of the CURRENT public API which are not in the BASELINE.
+fn public_api_additions<'a>(current: &'a str, baseline: &str) -> Vec<&'a str> {
+    let baseline: std::collections::HashSet<&str> = baseline.lines().map(str::trim).collect();
+    current
+        .lines()
+        .map(str::trim)
+        .filter(|item| !item.is_empty() && !baseline.contains(item))
+        .collect()
+}
+
+fn run_typos(config: Option<&Path>) -> anyhow::Result<()> {
+    let config_args = typos_config_args(config)?;
+    if !is_ci() {
+        ensure_cargo_crate_is_installed("typos-cli", None, Some(TYPOS_VERSION), false)?;
+    }
+    ensure_tool_available(
+        "typos",
+        &format!("Install it with 'cargo install typos-cli --version {TYPOS_VERSION}', see https://github.com/crate-ci/typos."),
+    )?;
+    group!("Typos");
+    let mut args = vec!["--diff", "--color", "always"];
+    args.extend(config_args.iter().map(String::as_str));
+    run_process("typos", &args, None, None, "Typos check execution failed")?;
+    endgroup!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn warning(package_id: &str, spans: &str) -> String {
+        format!(
+            r#"{{"reason":"compiler-message","package_id":"{package_id}","message":{{"level":"warning","spans":{spans}}}}}"#
+        )
+    }
+
+    #[rstest]
+    #[case::declared("[package]\nrust-version = \"1.79\"\n", "", Some("1.79"))]
+    #[case::inherited(
+        "[package]\nrust-version.workspace = true\n",
+        "[workspace.package]\nrust-version = \"1.75\"\n",
+        Some("1.75")
+    )]
+    #[case::inherited_but_missing("[package]\nrust-version = { workspace = true }\n", "", None)]
+    #[case::not_declared("[package]\nname = \"a\"\n", "", None)]
+    fn test_manifest_rust_version(
+        #[case] manifest: &str,
+        #[case] workspace: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let manifest: DocumentMut = manifest.parse().unwrap();
+        let workspace: DocumentMut = workspace.parse().unwrap();
+        assert_eq!(
+            manifest_rust_version(&manifest, &workspace).as_deref(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_count_warnings() {
+        let span = r#"[{"file_name":"src/lib.rs"}]"#;
+        let messages = [
+            warning("id-a", span),
+            warning("id-a", span),
+            warning("id-b", span),
+            warning("id-a", "[]"),
+            r#"{"reason":"compiler-message","package_id":"id-b","message":{"level":"error","spans":[{}]}}"#
+                .to_string(),
+            r#"{"reason":"build-finished","success":true}"#.to_string(),
+        ]
+        .join("\n");
+        let names = HashMap::from([("id-a".to_string(), "crate-a".to_string())]);
+        let counts = count_warnings(&messages, &names);
+        assert_eq!(
+            counts,
+            BTreeMap::from([("crate-a".to_string(), 2), ("id-b".to_string(), 1)])
+        );
+    }
+
+    #[rstest]
+    #[case::complete(
+        "[package]\nname = \"a\"\ndescription = \"A\"\nlicense.workspace = true\nrepository = \"url\"\n\n[dependencies]\nb = { path = \"../b\", version = \"1.0\" }\n\n[dev-dependencies]\nc = { path = \"../c\" }\n",
+        true,
+        0
+    )]
+    #[case::missing_fields("[package]\nname = \"a\"\nlicense-file = \"LICENSE\"\n", true, 2)]
+    #[case::wildcard_version(
+        "[package]\nname = \"a\"\n\n[dependencies]\nb = \"*\"\n\n[target.'cfg(unix)'.dependencies]\nc = { version = \"*\" }\n",
+        false,
+        2
+    )]
+    #[case::path_without_version(
+        "[package]\nname = \"a\"\ndescription = \"A\"\nlicense = \"MIT\"\nrepository = \"url\"\n\n[build-dependencies]\nb = { path = \"../b\" }\n",
+        true,
+        1
+    )]
+    #[case::not_publishable(
+        "[package]\nname = \"a\"\n\n[dependencies]\nb = { path = \"../b\" }\n",
+        false,
+        0
+    )]
+    fn test_lint_manifest(
+        #[case] manifest: &str,
+        #[case] publishable: bool,
+        #[case] expected_issues: usize,
+    ) {
+        let manifest: DocumentMut = manifest.parse().unwrap();
+        assert_eq!(lint_manifest(&manifest, publishable).len(), expected_issues);
+    }
+
+    #[test]
+    fn test_public_api_additions() {
+        let baseline = "pub fn crate_a::foo()\npub fn crate_a::removed()\n";
+        let current = "pub fn crate_a::foo()\npub fn crate_a::bar()\n\n";
+        assert_eq!(
+            public_api_additions(current, baseline),
+            vec!["pub fn crate_a::bar()"]
+        );
+    }
+
+    #[rstest]
+    #[case::same_counts(&[("a", 2)], &[("a", 2)], 0)]
+    #[case::fewer_warnings(&[("a", 1)], &[("a", 2)], 0)]
+    #[case::more_warnings(&[("a", 3)], &[("a", 2)], 1)]
+    #[case::new_crate(&[("a", 1), ("b", 1)], &[("a", 1)], 1)]
+    fn test_compare_with_baseline(
+        #[case] current: &[(&str, usize)],
+        #[case] baseline: &[(&str, usize)],
+        #[case] expected_regressions: usize,
+    ) {
+        let to_map = |entries: &[(&str, usize)]| {
+            entries
+                .iter()
+                .map(|(n, c)| (n.to_string(), *c))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let regressions = compare_with_baseline(&to_map(current), &to_map(baseline));
+        assert_eq!(regressions.len(), expected_regressions);
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Ok;
+use strum::IntoEnumIterator;
+
+use crate::{
+    endgroup, group,
+    utils::{
+        process::{run_process_for_package, run_process_for_workspace, CargoLockArgs},
+        workspace::{get_workspace_members, WorkspaceMemberType},
+    },
+};
+
+use super::Target;
+
+#[tracel_xtask_macros::declare_command_args(Target, None)]
+pub struct CleanCmdArgs {}
+
+pub fn handle_command(args: CleanCmdArgs) -> anyhow::Result<()> {
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    if args.target == Target::Workspace && (!args.exclude.is_empty() || !args.only.is_empty()) {
+        warn!("--target workspace cleans the whole workspace and ignores the arguments --exclude and --only. Use --target all-packages instead.");
+    }
+    run_clean(
+        &args.target,
+        &args.exclude,
+        &args.only,
+        args.doc,
+        args.release,
+        lock_args,
+    )
+}
+
+pub(crate) fn run_clean(
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+    doc: bool,
+    release: bool,
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    match target {
+        Target::Workspace => {
+            group!("Clean Workspace");
+            let mut cmd_args = vec!["clean"];
+            push_clean_args(&mut cmd_args, doc, release);
+            // 'cargo clean' does not support '--exclude'
+            run_process_for_workspace(
+                "cargo",
+                &lock_args.apply(&cmd_args),
+                None,
+                &[],
+                None,
+                None,
+                "Workspace clean failed",
+                None,
+                None,
+            )?;
+            endgroup!();
+        }
+        Target::Crates | Target::Examples => {
+            let members = match target {
+                Target::Crates => get_workspace_members(WorkspaceMemberType::Crate),
+                Target::Examples => get_workspace_members(WorkspaceMemberType::Example),
+                _ => unreachable!(),
+            };
+
+            for member in members {
+                group!("Clean: {}", member.name);
+                let mut cmd_args = vec!["clean", "-p", &member.name];
+                push_clean_args(&mut cmd_args, doc, release);
+                run_process_for_package(
+                    "cargo",
+                    &member.name,
+                    &lock_args.apply(&cmd_args),
+                    None,
+                    excluded,
+                    only,
+                    &format!("Clean failed for {}", &member.name),
+                    None,
+                    None,
+                )?;
+                endgroup!();
+            }
+        }
+        Target::AllPackages => {
+            Target::iter()
+                .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
+                .try_for_each(|t| run_clean(&t, excluded, only, doc, release, lock_args))?;
+        }
+    }
+    Ok(())
+}
+
+fn push_clean_args(cmd_args: &mut Vec<&str>, doc: bool, release: bool) {
+    if doc {
+        cmd_args.push("--doc");
+    }
+    if release {
+        cmd_args.push("--release");
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     commands::WARN_IGNORED_ONLY_ARGS,
     endgroup, group,
     utils::{
-        process::{run_process_for_package, run_process_for_workspace},
+        process::{run_process_for_package, run_process_for_workspace, CargoLockArgs},
         workspace::{get_workspace_members, WorkspaceMemberType},
     },
 };
@@ -16,23 +16,26 @@ use super::Target;
 pub struct CompileCmdArgs {}
 
 pub fn handle_command(args: CompileCmdArgs) -> anyhow::Result<()> {
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
     if args.target == Target::Workspace && !args.only.is_empty() {
         warn!("{}", WARN_IGNORED_ONLY_ARGS);
     }
-    run_compile(&args.target, &args.exclude, &args.only)
+    run_compile(&args.target, &args.exclude, &args.only, lock_args)
 }
 
 pub(crate) fn run_compile(
     target: &Target,
     excluded: &Vec<String>,
     only: &Vec<String>,
+    lock_args: CargoLockArgs,
 ) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Compile Workspace");
             run_process_for_workspace(
                 "cargo",
-                &["check", "--workspace"],
+                &lock_args.apply(&["check", "--workspace"]),
+                None,
                 excluded,
                 None,
                 None,
@@ -54,7 +57,8 @@ pub(crate) fn run_compile(
                 run_process_for_package(
                     "cargo",
                     &member.name,
-                    &["check", "-p", &member.name],
+                    &lock_args.apply(&["check", "-p", &member.name]),
+                    None,
                     excluded,
                     only,
                     &format!("Compilation failed for {}", &member.name),
@@ -67,7 +71,7 @@ pub(crate) fn run_compile(
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_compile(&t, excluded, only))?;
+                .try_for_each(|t| run_compile(&t, excluded, only, lock_args))?;
         }
     }
     Ok(())
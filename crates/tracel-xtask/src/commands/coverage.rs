@@ -1,10 +1,14 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Ok;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use strum::{Display, EnumString};
 
 use crate::{
     endgroup, group,
     utils::{
-        cargo::ensure_cargo_crate_is_installed, process::run_process, rustup::rustup_add_component,
+        cargo::ensure_cargo_crate_is_installed, ci::is_ci, git::display_relative_to_root,
+        process::run_process, rustup::rustup_add_component, workspace::get_target_directory,
     },
     versions::GRCOV_VERSION,
 };
@@ -22,9 +26,10 @@ impl Default for CoverageSubCommand {
 
 #[derive(Args, Default, Clone, PartialEq)]
 pub struct GenerateCmdArgs {
-    /// Build profile to use.
+    /// Build profile of the instrumented test binaries, either 'debug' or 'release'.
+    /// Use 'release' for tests run with 'cargo xtask -c test --release'.
     #[arg(short, long, value_enum, default_value_t = Profile::default())]
-    profile: Profile,
+    pub profile: Profile,
     /// Comma-separated list of excluded crates.
     #[arg(
         short = 'i',
@@ -34,41 +39,272 @@ pub struct GenerateCmdArgs {
         required = false
     )]
     pub ignore: Vec<String>,
+    /// Glob pattern of source files to exclude from the report, can be repeated.
+    #[arg(long = "exclude-files", value_name = "GLOB")]
+    pub exclude_files: Vec<String>,
+    /// Format of the coverage report.
+    #[arg(long, value_enum, default_value_t = CoverageFormat::default())]
+    pub format: CoverageFormat,
+    /// Path of the coverage report, a directory for the 'html' format.
+    /// Defaults to 'lcov.info', 'cobertura.xml' or 'target/coverage/html' depending on the format.
+    #[arg(long = "output-path", value_name = "PATH")]
+    pub output_path: Option<PathBuf>,
+    /// Fail if the line coverage of the lcov report is below this percentage.
+    #[arg(long = "fail-under", value_name = "PCT")]
+    pub fail_under: Option<f64>,
+}
+
+/// Output format of the coverage report generated by grcov.
+#[derive(EnumString, Display, Default, Clone, Copy, Debug, PartialEq, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum CoverageFormat {
+    /// lcov tracefile, supported by most coverage services.
+    #[default]
+    Lcov,
+    /// Cobertura XML report.
+    Cobertura,
+    /// HTML report for local browsing.
+    Html,
+}
+
+impl CoverageFormat {
+    /// Return the default path of the report for this format.
+    fn default_output_path(&self) -> PathBuf {
+        match self {
+            CoverageFormat::Lcov => PathBuf::from("lcov.info"),
+            CoverageFormat::Cobertura => PathBuf::from("cobertura.xml"),
+            CoverageFormat::Html => PathBuf::from("target/coverage/html"),
+        }
+    }
+}
+
+#[derive(Args, Default, Clone, PartialEq)]
+pub struct CleanCmdArgs {
+    /// Only list the profraw files that would be removed.
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 pub fn handle_command(args: CoverageCmdArgs) -> anyhow::Result<()> {
     match args.get_command() {
         CoverageSubCommand::Install => install_grcov(),
         CoverageSubCommand::Generate(gen_args) => run_grcov(&gen_args),
+        CoverageSubCommand::Clean(clean_args) => clean_profraw_files(&clean_args),
     }
 }
 
-fn install_grcov() -> anyhow::Result<()> {
+/// Instrument the code compiled afterwards by this process for coverage.
+pub(crate) fn setup_coverage() -> anyhow::Result<()> {
+    unsafe {
+        std::env::set_var("RUSTFLAGS", "-Cinstrument-coverage");
+        std::env::set_var("LLVM_PROFILE_FILE", "burn-%p-%m.profraw");
+    }
+    Ok(())
+}
+
+pub(crate) fn install_grcov() -> anyhow::Result<()> {
     rustup_add_component("llvm-tools-preview")?;
-    if std::env::var("CI").is_err() {
+    if !is_ci() {
         ensure_cargo_crate_is_installed("grcov", None, Some(GRCOV_VERSION), false)?;
     }
     Ok(())
 }
 
-fn run_grcov(generate_args: &GenerateCmdArgs) -> anyhow::Result<()> {
+pub(crate) fn run_grcov(generate_args: &GenerateCmdArgs) -> anyhow::Result<()> {
+    if generate_args.fail_under.is_some() && generate_args.format != CoverageFormat::Lcov {
+        return Err(anyhow::anyhow!(
+            "'--fail-under' requires the 'lcov' format to compute the line coverage."
+        ));
+    }
+    let binary_path = coverage_binary_path(&generate_args.profile)?;
+    let format = generate_args.format.to_string();
+    let output_path = generate_args
+        .output_path
+        .clone()
+        .unwrap_or_else(|| generate_args.format.default_output_path());
+    if generate_args.format == CoverageFormat::Html {
+        std::fs::create_dir_all(&output_path)?;
+    } else if let Some(dir) = output_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let output = output_path.to_string_lossy();
     group!("Grcov");
-    let binary_path = format!("./target/{}/", generate_args.profile);
     #[rustfmt::skip]
     let mut args = vec![
         ".",
         "--binary-path", &binary_path,
         "-s", ".",
-        "-t", "lcov",
-        "-o", "lcov.info",
+        "-t", &format,
+        "-o", &output,
         "--branch",
         "--ignore-not-existing",
     ];
     generate_args
         .ignore
         .iter()
+        .chain(generate_args.exclude_files.iter())
         .for_each(|i| args.extend(vec!["--ignore", i]));
     run_process("grcov", &args, None, None, "Error executing grcov")?;
+    info!(
+        "Coverage report written to {}",
+        display_relative_to_root(&output_path)
+    );
+    endgroup!();
+    if let Some(threshold) = generate_args.fail_under {
+        check_line_coverage(&output_path, threshold)?;
+    }
+    Ok(())
+}
+
+/// Fail if the line coverage of the lcov report at PATH is below THRESHOLD percent.
+fn check_line_coverage(path: &Path, threshold: f64) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let coverage = lcov_line_coverage(&content)
+        .ok_or_else(|| anyhow::anyhow!("No line coverage data found in {}", path.display()))?;
+    if coverage < threshold {
+        return Err(anyhow::anyhow!(
+            "Line coverage is {coverage:.2}%, the minimum required is {threshold:.2}%."
+        ));
+    }
+    info!("Line coverage is {coverage:.2}% (minimum required: {threshold:.2}%).");
+    Ok(())
+}
+
+/// Return the percentage of lines hit in the lcov CONTENT computed from its 'DA' records,
+/// or None if there are no instrumented lines.
+fn lcov_line_coverage(content: &str) -> Option<f64> {
+    let (hit, total) = content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("DA:"))
+        .filter_map(|record| record.split(',').nth(1)?.parse::<u64>().ok())
+        .fold((0u64, 0u64), |(hit, total), count| {
+            (hit + u64::from(count > 0), total + 1)
+        });
+    (total > 0).then(|| hit as f64 * 100.0 / total as f64)
+}
+
+pub(crate) fn clean_profraw_files(clean_args: &CleanCmdArgs) -> anyhow::Result<()> {
+    group!("Clean profraw files");
+    // instrumented test binaries run from their package directory so the profraw files can be
+    // anywhere below the current directory, the target directory may be outside of it with
+    // CARGO_TARGET_DIR so it is walked separately.
+    let target_dir = get_target_directory();
+    let target_dir = target_dir.canonicalize().unwrap_or(target_dir);
+    let mut files = vec![];
+    collect_profraw_files(
+        &std::env::current_dir()?.canonicalize()?,
+        &target_dir,
+        &mut files,
+    )?;
+    if target_dir.is_dir() {
+        collect_profraw_files(&target_dir, Path::new(""), &mut files)?;
+    }
+    for file in &files {
+        if clean_args.dry_run {
+            info!("Would remove {}", display_relative_to_root(file));
+        } else {
+            std::fs::remove_file(file)?;
+        }
+    }
+    if clean_args.dry_run {
+        info!("{} profraw file(s) would be removed.", files.len());
+    } else {
+        info!("Removed {} profraw file(s).", files.len());
+    }
     endgroup!();
     Ok(())
 }
+
+/// Collect the profraw files of DIR and of its subdirectories except SKIPPED_DIR.
+/// Symbolic links are not followed so that files outside of DIR are never collected.
+fn collect_profraw_files(
+    dir: &Path,
+    skipped_dir: &Path,
+    files: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if path != skipped_dir && path.file_name().is_some_and(|name| name != ".git") {
+                collect_profraw_files(&path, skipped_dir, files)?;
+            }
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "profraw") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Return the path of the instrumented binaries for the given profile in the target directory.
+/// The profraw files must have been produced by test binaries built with the same profile.
+fn coverage_binary_path(profile: &Profile) -> anyhow::Result<String> {
+    if *profile == Profile::All {
+        return Err(anyhow::anyhow!(
+            "Coverage can only be generated for a single profile, use either 'debug' or 'release'."
+        ));
+    }
+    let binary_path = get_target_directory().join(profile.to_string());
+    if !binary_path.is_dir() {
+        return Err(anyhow::anyhow!(
+            "No '{}' build found in '{}', run the instrumented tests with the '{}' profile first.",
+            profile,
+            binary_path.display(),
+            profile
+        ));
+    }
+    Ok(binary_path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::lcov(CoverageFormat::Lcov, "lcov", "lcov.info")]
+    #[case::cobertura(CoverageFormat::Cobertura, "cobertura", "cobertura.xml")]
+    #[case::html(CoverageFormat::Html, "html", "target/coverage/html")]
+    fn test_coverage_format(
+        #[case] format: CoverageFormat,
+        #[case] grcov_type: &str,
+        #[case] path: &str,
+    ) {
+        assert_eq!(format.to_string(), grcov_type);
+        assert_eq!(format.default_output_path(), PathBuf::from(path));
+    }
+
+    #[test]
+    fn test_collect_profraw_files() {
+        let root = std::env::temp_dir().join(format!("xtask-profraw-{}", std::process::id()));
+        let target = root.join("target");
+        std::fs::create_dir_all(root.join("crate").join("src")).unwrap();
+        std::fs::create_dir_all(target.join("debug")).unwrap();
+        std::fs::write(root.join("crate").join("burn-1.profraw"), "").unwrap();
+        std::fs::write(root.join("crate").join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(target.join("debug").join("burn-2.profraw"), "").unwrap();
+        // a symbolic link to a parent directory must not be followed
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("crate").join("parent")).unwrap();
+        let mut files = vec![];
+        let result = collect_profraw_files(&root, &target, &mut files);
+        std::fs::remove_dir_all(&root).unwrap();
+        result.unwrap();
+        assert_eq!(files, vec![root.join("crate").join("burn-1.profraw")]);
+    }
+
+    #[rstest]
+    #[case::empty("", None)]
+    #[case::no_lines("SF:src/lib.rs\nend_of_record\n", None)]
+    #[case::all_hit("SF:src/lib.rs\nDA:1,3\nDA:2,1\nend_of_record\n", Some(100.0))]
+    #[case::with_checksum("DA:1,0,abc\nDA:2,5,def\n", Some(50.0))]
+    #[case::multiple_files(
+        "SF:a.rs\nDA:1,1\nDA:2,0\nend_of_record\nSF:b.rs\nDA:1,0\nDA:2,0\nend_of_record\n",
+        Some(25.0)
+    )]
+    fn test_lcov_line_coverage(#[case] content: &str, #[case] expected: Option<f64>) {
+        assert_eq!(lcov_line_coverage(content), expected);
+    }
+}
@@ -1,12 +1,30 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Command, Stdio},
+};
+
 use anyhow::Ok;
 use strum::IntoEnumIterator;
 
 use crate::{
-    commands::WARN_IGNORED_ONLY_ARGS,
-    endgroup, group,
+    commands::{WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS, WARN_IGNORED_ONLY_ARGS},
+    endgroup,
+    errors::spawn_error,
+    group, group_info,
     utils::{
-        process::{run_process_for_package, run_process_for_workspace},
-        workspace::{get_workspace_members, WorkspaceMember, WorkspaceMemberType},
+        browser,
+        git::display_relative_to_root,
+        process::{
+            remove_ansi_codes, run_process, run_process_for_package, run_process_for_workspace,
+            with_cargo_extra_args, CargoLockArgs,
+        },
+        workspace::{
+            get_publishable_workspace_members, get_target_directory, get_workspace_members,
+            WorkspaceMember, WorkspaceMemberType,
+        },
     },
 };
 
@@ -16,26 +34,106 @@ use super::Target;
 pub struct DocCmdArgs {}
 
 pub fn handle_command(args: DocCmdArgs) -> anyhow::Result<()> {
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    let feature_args = doc_feature_args(&args);
     if args.target == Target::Workspace && !args.only.is_empty() {
         warn!("{}", WARN_IGNORED_ONLY_ARGS);
     }
+    // the flags are only set in the environment of the cargo processes
+    let rustdocflags = args
+        .deny_warnings
+        .then(|| merge_rustdocflags(std::env::var("RUSTDOCFLAGS").ok().as_deref(), "-D warnings"));
+    let rustdocflags = rustdocflags.as_deref();
     match args.get_command() {
-        DocSubCommand::Build => run_documentation_build(&args.target, &args.exclude, &args.only),
-        DocSubCommand::Tests => run_documentation(&args.target, &args.exclude, &args.only),
+        DocSubCommand::Build => {
+            match args.max_warnings {
+                Some(max) => {
+                    if args.target != Target::Workspace || !args.only.is_empty() {
+                        warn!("{}", WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS);
+                    }
+                    run_documentation_build_with_max_warnings(
+                        &args.exclude,
+                        max,
+                        &feature_args,
+                        lock_args,
+                    )?;
+                }
+                None => run_documentation_build(
+                    &args.target,
+                    &args.exclude,
+                    &args.only,
+                    &feature_args,
+                    lock_args,
+                    rustdocflags,
+                )?,
+            }
+            if args.require_docs {
+                run_missing_docs_check(
+                    &args.target,
+                    &args.exclude,
+                    &args.only,
+                    &feature_args,
+                    lock_args,
+                    rustdocflags,
+                )?;
+            }
+            if let Some(dir) = &args.output_dir {
+                copy_documentation(dir)?;
+            }
+            if args.open {
+                let doc_dir = args
+                    .output_dir
+                    .clone()
+                    .unwrap_or_else(|| get_target_directory().join("doc"));
+                open_documentation(&doc_dir, &args.target, &args.exclude, &args.only)?;
+            }
+            Ok(())
+        }
+        DocSubCommand::Tests => run_documentation(
+            &args.target,
+            &args.exclude,
+            &args.only,
+            &feature_args,
+            lock_args,
+            rustdocflags,
+        ),
     }
 }
 
+/// Return the cargo feature arguments of the documentation builds and tests.
+fn doc_feature_args(args: &DocCmdArgs) -> Vec<String> {
+    let mut feature_args = vec![];
+    if let Some(features) = &args.features {
+        if !features.is_empty() {
+            feature_args.extend(["--features".to_string(), features.join(",")]);
+        }
+    }
+    if args.no_default_features {
+        feature_args.push("--no-default-features".to_string());
+    }
+    if args.all_features {
+        feature_args.push("--all-features".to_string());
+    }
+    feature_args
+}
+
 fn run_documentation_build(
     target: &Target,
     excluded: &Vec<String>,
     only: &Vec<String>,
+    feature_args: &[String],
+    lock_args: CargoLockArgs,
+    rustdocflags: Option<&str>,
 ) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Build Workspace documentation");
+            let mut cmd_args = vec!["doc", "--workspace", "--no-deps", "--color=always"];
+            cmd_args.extend(feature_args.iter().map(String::as_str));
             run_process_for_workspace(
                 "cargo",
-                &["doc", "--workspace", "--no-deps", "--color=always"],
+                &lock_args.apply(&cmd_args),
+                rustdocflags_env(rustdocflags),
                 excluded,
                 None,
                 None,
@@ -54,10 +152,13 @@ fn run_documentation_build(
 
             for member in members {
                 group!("Doc Build: {}", member.name);
+                let mut cmd_args = vec!["doc", "-p", &member.name, "--no-deps", "--color=always"];
+                cmd_args.extend(feature_args.iter().map(String::as_str));
                 run_process_for_package(
                     "cargo",
                     &member.name,
-                    &["doc", "-p", &member.name, "--no-deps", "--color=always"],
+                    &lock_args.apply(&cmd_args),
+                    rustdocflags_env(rustdocflags),
                     excluded,
                     only,
                     &format!("Format check execution failed for {}", &member.name),
@@ -70,23 +171,212 @@ fn run_documentation_build(
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_documentation_build(&t, excluded, only))?;
+                .try_for_each(|t| {
+                    run_documentation_build(
+                        &t,
+                        excluded,
+                        only,
+                        feature_args,
+                        lock_args,
+                        rustdocflags,
+                    )
+                })?;
         }
     }
     Ok(())
 }
 
+/// Copy the generated documentation to DIR.
+fn copy_documentation(dir: &Path) -> anyhow::Result<()> {
+    let doc_dir = get_target_directory().join("doc");
+    group!("Copy documentation to {}", display_relative_to_root(dir));
+    copy_dir_all(&doc_dir, dir).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to copy documentation from {} to {}: {}",
+            doc_dir.display(),
+            dir.display(),
+            e
+        )
+    })?;
+    endgroup!();
+    Ok(())
+}
+
+/// Open the documentation of DOC_DIR in a browser, the location is printed on headless environments. Like 'cargo doc --open', this is the index
+/// page of the first documented package.
+fn open_documentation(
+    doc_dir: &Path,
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+) -> anyhow::Result<()> {
+    let members = match target {
+        Target::Examples => get_workspace_members(WorkspaceMemberType::Example),
+        _ => get_workspace_members(WorkspaceMemberType::Crate),
+    };
+    // workspace target ignores the --only argument
+    let only: &[String] = if *target == Target::Workspace {
+        &[]
+    } else {
+        only
+    };
+    let index = members
+        .iter()
+        .filter(|m| !excluded.contains(&m.name) && (only.is_empty() || only.contains(&m.name)))
+        .map(|m| doc_dir.join(m.name.replace('-', "_")).join("index.html"))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| doc_dir.join("index.html"));
+    browser::open(&index.to_string_lossy())
+}
+
+fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    std::result::Result::Ok(())
+}
+
+/// Build the workspace documentation and fail if rustdoc reports more than MAX warnings.
+fn run_documentation_build_with_max_warnings(
+    excluded: &[String],
+    max: usize,
+    feature_args: &[String],
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    group!("Build Workspace documentation");
+    let mut args = vec!["doc", "--workspace", "--no-deps", "--color=always"];
+    args.extend(feature_args.iter().map(String::as_str));
+    excluded
+        .iter()
+        .for_each(|ex| args.extend(["--exclude", ex.as_str()]));
+    let args = with_cargo_extra_args(&lock_args.apply(&args));
+    group_info!("Command line: cargo {}", args.join(" "));
+    let mut child = Command::new("cargo")
+        .args(&args)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo doc"))?;
+    let mut count = 0;
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if is_rustdoc_warning(&line) {
+                count += 1;
+            }
+            eprintln!("{}", line);
+        }
+    }
+    let status = child
+        .wait()
+        .map_err(|e| anyhow::anyhow!("Failed to wait for cargo doc: {}", e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Workspace documentation build failed"));
+    }
+    if count > max {
+        return Err(anyhow::anyhow!(
+            "Documentation build reported {count} warnings, the maximum allowed is {max}."
+        ));
+    }
+    info!("Documentation build reported {count} warnings (maximum allowed: {max}).");
+    endgroup!();
+    Ok(())
+}
+
+/// Return true if LINE is a rustdoc warning, the 'generated N warnings' summaries are not counted.
+fn is_rustdoc_warning(line: &str) -> bool {
+    let line = remove_ansi_codes(line);
+    line.starts_with("warning:") && !line.starts_with("warning: `")
+}
+
+/// Append FLAGS to the EXISTING rustdoc flags.
+fn merge_rustdocflags(existing: Option<&str>, flags: &str) -> String {
+    match existing {
+        Some(existing) if !existing.trim().is_empty() => format!("{} {}", existing.trim(), flags),
+        _ => flags.to_string(),
+    }
+}
+
+/// Return the environment of the cargo processes setting RUSTDOCFLAGS, if any.
+fn rustdocflags_env(rustdocflags: Option<&str>) -> Option<HashMap<&str, &str>> {
+    rustdocflags.map(|flags| HashMap::from([("RUSTDOCFLAGS", flags)]))
+}
+
+/// Build the documentation of the publishable crates with the `missing_docs` lint denied.
+/// Internal crates (with `publish = false`) are not checked.
+fn run_missing_docs_check(
+    target: &Target,
+    excluded: &[String],
+    only: &[String],
+    feature_args: &[String],
+    lock_args: CargoLockArgs,
+    rustdocflags: Option<&str>,
+) -> anyhow::Result<()> {
+    let (members, only) = match target {
+        Target::Crates => (get_workspace_members(WorkspaceMemberType::Crate), only),
+        Target::Examples => (get_workspace_members(WorkspaceMemberType::Example), only),
+        Target::AllPackages | Target::Workspace => {
+            let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+            members.extend(get_workspace_members(WorkspaceMemberType::Example));
+            // workspace target ignores the --only argument
+            let only: &[String] = if *target == Target::Workspace {
+                &[]
+            } else {
+                only
+            };
+            (members, only)
+        }
+    };
+    let rustdocflags = merge_rustdocflags(
+        rustdocflags
+            .map(String::from)
+            .or_else(|| std::env::var("RUSTDOCFLAGS").ok())
+            .as_deref(),
+        "-D missing_docs",
+    );
+    let publishable = get_publishable_workspace_members();
+    for member in members.iter().filter(|m| publishable.contains(&m.name)) {
+        if excluded.contains(&member.name) || (!only.is_empty() && !only.contains(&member.name)) {
+            group_info!("Skip '{}' because it has been excluded!", &member.name);
+            continue;
+        }
+        group!("Doc Missing Docs: {}", member.name);
+        let mut cmd_args = vec!["doc", "-p", &member.name, "--no-deps", "--color=always"];
+        cmd_args.extend(feature_args.iter().map(String::as_str));
+        run_process(
+            "cargo",
+            &lock_args.apply(&cmd_args),
+            Some(HashMap::from([("RUSTDOCFLAGS", rustdocflags.as_str())])),
+            None,
+            &format!("Missing documentation found in {}", &member.name),
+        )?;
+        endgroup!();
+    }
+    Ok(())
+}
+
 pub(crate) fn run_documentation(
     target: &Target,
     excluded: &[String],
     only: &[String],
+    feature_args: &[String],
+    lock_args: CargoLockArgs,
+    rustdocflags: Option<&str>,
 ) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Workspace Documentation Tests");
+            let mut cmd_args = vec!["test", "--workspace", "--doc", "--color", "always"];
+            cmd_args.extend(feature_args.iter().map(String::as_str));
             run_process_for_workspace(
                 "cargo",
-                &["test", "--workspace", "--doc", "--color", "always"],
+                &lock_args.apply(&cmd_args),
+                rustdocflags_env(rustdocflags),
                 excluded,
                 Some(r"Doc-tests (\w+)"),
                 Some("Doc Tests"),
@@ -104,13 +394,22 @@ pub(crate) fn run_documentation(
             };
 
             for member in members {
-                run_doc_test(&member, excluded, only)?;
+                run_doc_test(
+                    &member,
+                    excluded,
+                    only,
+                    feature_args,
+                    lock_args,
+                    rustdocflags,
+                )?;
             }
         }
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_documentation(&t, excluded, only))?;
+                .try_for_each(|t| {
+                    run_documentation(&t, excluded, only, feature_args, lock_args, rustdocflags)
+                })?;
         }
     }
     Ok(())
@@ -120,12 +419,18 @@ fn run_doc_test(
     member: &WorkspaceMember,
     excluded: &[String],
     only: &[String],
+    feature_args: &[String],
+    lock_args: CargoLockArgs,
+    rustdocflags: Option<&str>,
 ) -> Result<(), anyhow::Error> {
     group!("Doc Tests: {}", member.name);
+    let mut cmd_args = vec!["test", "--doc", "-p", &member.name];
+    cmd_args.extend(feature_args.iter().map(String::as_str));
     run_process_for_package(
         "cargo",
         &member.name,
-        &["test", "--doc", "-p", &member.name],
+        &lock_args.apply(&cmd_args),
+        rustdocflags_env(rustdocflags),
         excluded,
         only,
         &format!(
@@ -141,3 +446,26 @@ fn run_doc_test(
     endgroup!();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::warning("warning: unresolved link to `Foo`", true)]
+    #[case::colored_warning("\x1b[1m\x1b[33mwarning\x1b[0m\x1b[1m:\x1b[0m unresolved link", true)]
+    #[case::summary("warning: `tracel-xtask` (lib doc) generated 2 warnings", false)]
+    #[case::other_line("   Documenting tracel-xtask v1.1.8", false)]
+    fn test_is_rustdoc_warning(#[case] line: &str, #[case] expected: bool) {
+        assert_eq!(is_rustdoc_warning(line), expected);
+    }
+
+    #[rstest]
+    #[case::unset(None, "-D warnings")]
+    #[case::empty(Some("  "), "-D warnings")]
+    #[case::existing(Some("--cfg docsrs "), "--cfg docsrs -D warnings")]
+    fn test_merge_rustdocflags(#[case] existing: Option<&str>, #[case] expected: &str) {
+        assert_eq!(merge_rustdocflags(existing, "-D warnings"), expected);
+    }
+}
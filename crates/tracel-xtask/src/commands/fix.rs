@@ -1,14 +1,21 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
 use anyhow::{Ok, Result};
 use strum::IntoEnumIterator;
 
 use crate::{
-    commands::WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS,
+    commands::{typos_config_args, WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS},
     endgroup, group,
     utils::{
         cargo::ensure_cargo_crate_is_installed,
-        process::{run_process, run_process_for_package, run_process_for_workspace},
+        git::{changed_files_since, workspace_files_by_package},
+        process::{run_process, run_process_for_package, run_process_for_workspace, CargoLockArgs},
         prompt::ask_once,
-        workspace::{get_workspace_members, WorkspaceMemberType},
+        tool::ensure_tool_available,
+        workspace::{get_package_editions, get_workspace_members, WorkspaceMemberType},
     },
     versions::TYPOS_VERSION,
 };
@@ -19,6 +26,8 @@ use super::Target;
 pub struct FixCmdArgs {}
 
 pub fn handle_command(args: FixCmdArgs, mut answer: Option<bool>) -> anyhow::Result<()> {
+    // only the clippy invocations of the lint fix accept the lockfile and network flags
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
     if answer.is_none() {
         if args.target == Target::Workspace && (!args.exclude.is_empty() || !args.only.is_empty()) {
             warn!("{}", WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS);
@@ -30,9 +39,15 @@ pub fn handle_command(args: FixCmdArgs, mut answer: Option<bool>) -> anyhow::Res
     if answer.unwrap() {
         match args.get_command() {
             FixSubCommand::Audit => run_audit(),
-            FixSubCommand::Format => run_format(&args.target, &args.exclude, &args.only),
-            FixSubCommand::Lint => run_lint(&args.target, &args.exclude, &args.only),
-            FixSubCommand::Typos => run_typos(),
+            FixSubCommand::Format => match &args.since {
+                Some(reference) => run_format_since(reference, &args.exclude, &args.only),
+                None => run_format(&args.target, &args.exclude, &args.only),
+            },
+            FixSubCommand::Lint => match &args.since {
+                Some(reference) => run_lint_since(reference, &args.exclude, &args.only, lock_args),
+                None => run_lint(&args.target, &args.exclude, &args.only, lock_args),
+            },
+            FixSubCommand::Typos => run_typos(args.typos_config.as_deref()),
             FixSubCommand::All => FixSubCommand::iter()
                 .filter(|c| *c != FixSubCommand::All)
                 .try_for_each(|c| {
@@ -42,6 +57,11 @@ pub fn handle_command(args: FixCmdArgs, mut answer: Option<bool>) -> anyhow::Res
                             target: args.target.clone(),
                             exclude: args.exclude.clone(),
                             only: args.only.clone(),
+                            locked: args.locked,
+                            frozen: args.frozen,
+                            offline: args.offline,
+                            typos_config: args.typos_config.clone(),
+                            since: args.since.clone(),
                         },
                         answer,
                     )
@@ -73,6 +93,7 @@ fn run_format(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> Re
             run_process_for_workspace(
                 "cargo",
                 &["fmt"],
+                None,
                 &[],
                 None,
                 None,
@@ -94,6 +115,7 @@ fn run_format(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> Re
                     "cargo",
                     &member.name,
                     &["fmt", "-p", &member.name],
+                    None,
                     excluded,
                     only,
                     &format!("Format check execution failed for {}", &member.name),
@@ -112,13 +134,18 @@ fn run_format(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> Re
     Ok(())
 }
 
-fn run_lint(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> anyhow::Result<()> {
+fn run_lint(
+    target: &Target,
+    excluded: &Vec<String>,
+    only: &Vec<String>,
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
     match target {
         Target::Workspace => {
             group!("Lint Workspace");
             run_process_for_workspace(
                 "cargo",
-                &[
+                &lock_args.apply(&[
                     "clippy",
                     "--no-deps",
                     "--fix",
@@ -128,7 +155,8 @@ fn run_lint(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> anyh
                     "--",
                     "--deny",
                     "warnings",
-                ],
+                ]),
+                None,
                 &[],
                 None,
                 None,
@@ -145,47 +173,119 @@ fn run_lint(target: &Target, excluded: &Vec<String>, only: &Vec<String>) -> anyh
                 _ => unreachable!(),
             };
             for member in members {
-                group!("Lint: {}", member.name);
-                run_process_for_package(
-                    "cargo",
-                    &member.name,
-                    &[
-                        "clippy",
-                        "--no-deps",
-                        "--fix",
-                        "--allow-dirty",
-                        "--allow-staged",
-                        "--color=always",
-                        "-p",
-                        &member.name,
-                        "--",
-                        "--deny",
-                        "warnings",
-                    ],
-                    excluded,
-                    only,
-                    &format!("Lint fix execution failed for {}", &member.name),
-                    None,
-                    None,
-                )?;
-                endgroup!();
+                run_lint_package(&member.name, excluded, only, lock_args)?;
             }
         }
         Target::AllPackages => {
             Target::iter()
                 .filter(|t| *t != Target::AllPackages && *t != Target::Workspace)
-                .try_for_each(|t| run_lint(&t, excluded, only))?;
+                .try_for_each(|t| run_lint(&t, excluded, only, lock_args))?;
+        }
+    }
+    Ok(())
+}
+
+fn run_lint_package(
+    package: &String,
+    excluded: &[String],
+    only: &[String],
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    group!("Lint: {}", package);
+    run_process_for_package(
+        "cargo",
+        package,
+        &lock_args.apply(&[
+            "clippy",
+            "--no-deps",
+            "--fix",
+            "--allow-dirty",
+            "--allow-staged",
+            "--color=always",
+            "-p",
+            package,
+            "--",
+            "--deny",
+            "warnings",
+        ]),
+        None,
+        excluded,
+        only,
+        &format!("Lint fix execution failed for {}", package),
+        None,
+        None,
+    )?;
+    endgroup!();
+    Ok(())
+}
+
+/// Return the Rust files changed since the given git reference grouped by their package.
+/// Deleted files are left out.
+fn changed_rust_files_by_package(
+    reference: &str,
+) -> anyhow::Result<BTreeMap<String, Vec<PathBuf>>> {
+    let files: Vec<PathBuf> = changed_files_since(reference)?
+        .into_iter()
+        .filter(|f| f.extension().is_some_and(|ext| ext == "rs") && f.is_file())
+        .collect();
+    if files.is_empty() {
+        info!("No Rust file changed since '{}'.", reference);
+    }
+    Ok(workspace_files_by_package(&files))
+}
+
+fn run_format_since(reference: &str, excluded: &[String], only: &[String]) -> anyhow::Result<()> {
+    let packages = changed_rust_files_by_package(reference)?;
+    let editions = get_package_editions();
+    for (package, files) in packages {
+        group!("Format: {}", package);
+        if excluded.contains(&package) || (!only.is_empty() && !only.contains(&package)) {
+            info!("Skip '{}' because it has been excluded!", package);
+            endgroup!();
+            continue;
         }
+        // rustfmt does not read the edition from the manifest like 'cargo fmt' does
+        let mut args = vec![
+            "--edition",
+            editions.get(&package).map_or("2021", |e| e.as_str()),
+        ];
+        args.extend(files.iter().filter_map(|f| f.to_str()));
+        run_process(
+            "rustfmt",
+            &args,
+            None,
+            None,
+            &format!("Format execution failed for {}", package),
+        )?;
+        endgroup!();
     }
     Ok(())
 }
 
-pub(crate) fn run_typos() -> anyhow::Result<()> {
+fn run_lint_since(
+    reference: &str,
+    excluded: &[String],
+    only: &[String],
+    lock_args: CargoLockArgs,
+) -> anyhow::Result<()> {
+    changed_rust_files_by_package(reference)?
+        .keys()
+        .try_for_each(|package| run_lint_package(package, excluded, only, lock_args))
+}
+
+pub(crate) fn run_typos(config: Option<&Path>) -> anyhow::Result<()> {
+    let config_args = typos_config_args(config)?;
     ensure_cargo_crate_is_installed("typos-cli", None, Some(TYPOS_VERSION), false)?;
+    ensure_tool_available(
+        "typos",
+        &format!("Install it with 'cargo install typos-cli --version {TYPOS_VERSION}', see https://github.com/crate-ci/typos."),
+    )?;
     group!("Typos");
+    let mut args = vec!["--write-changes", "--color", "always"];
+    args.extend(config_args.iter().map(String::as_str));
     run_process(
         "typos",
-        &["--write-changes", "--color", "always"],
+        &args,
         None,
         None,
         "Some typos have been found and cannot be fixed.",
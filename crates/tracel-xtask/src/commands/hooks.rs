@@ -0,0 +1,119 @@
+use std::{fs, path::Path};
+
+use anyhow::Ok;
+
+use crate::{
+    endgroup, group,
+    utils::git::{display_relative_to_root, get_hooks_dir},
+};
+
+/// Marker used to recognize the hooks installed by this command.
+const HOOK_MARKER: &str = "# Installed by 'cargo xtask install-hooks'";
+const BACKUP_EXTENSION: &str = "bak";
+
+#[tracel_xtask_macros::declare_command_args(None, None)]
+pub struct InstallHooksCmdArgs {}
+
+pub fn handle_command(args: InstallHooksCmdArgs) -> anyhow::Result<()> {
+    let hooks_dir = get_hooks_dir()?;
+    let mut hooks = vec!["pre-push"];
+    if args.pre_commit || args.uninstall {
+        hooks.push("pre-commit");
+    }
+    for hook in hooks {
+        let path = hooks_dir.join(hook);
+        if args.uninstall {
+            group!("Uninstall git hook: {}", hook);
+            uninstall_hook(&path)?;
+        } else {
+            group!("Install git hook: {}", hook);
+            install_hook(&path)?;
+        }
+        endgroup!();
+    }
+    Ok(())
+}
+
+fn install_hook(path: &Path) -> anyhow::Result<()> {
+    if path.exists() && !is_xtask_hook(path) {
+        let backup = path.with_extension(BACKUP_EXTENSION);
+        // the backup is restored on uninstall so a previous one must never be overwritten
+        if backup.exists() {
+            return Err(anyhow::anyhow!(
+                "Cannot back up the existing hook {} because {} already exists, move or remove it first.",
+                display_relative_to_root(path),
+                display_relative_to_root(&backup)
+            ));
+        }
+        fs::rename(path, &backup)?;
+        info!(
+            "Existing hook backed up to {}",
+            display_relative_to_root(&backup)
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        format!("#!/bin/sh\n{HOOK_MARKER}\nexec cargo xtask validate --profile fast\n"),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+    info!("Hook written to {}", display_relative_to_root(path));
+    Ok(())
+}
+
+fn uninstall_hook(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        info!("No hook found at {}", display_relative_to_root(path));
+        return Ok(());
+    }
+    if !is_xtask_hook(path) {
+        warn!(
+            "Hook {} has not been installed by xtask, leaving it untouched.",
+            display_relative_to_root(path)
+        );
+        return Ok(());
+    }
+    fs::remove_file(path)?;
+    info!("Hook {} removed", display_relative_to_root(path));
+    let backup = path.with_extension(BACKUP_EXTENSION);
+    if backup.exists() {
+        fs::rename(&backup, path)?;
+        info!(
+            "Previous hook restored from {}",
+            display_relative_to_root(&backup)
+        );
+    }
+    Ok(())
+}
+
+fn is_xtask_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(HOOK_MARKER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_hook_keeps_existing_backup() {
+        let dir = std::env::temp_dir().join(format!("xtask-hooks-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pre-push");
+        let backup = path.with_extension(BACKUP_EXTENSION);
+        fs::write(&path, "#!/bin/sh\necho current\n").unwrap();
+        fs::write(&backup, "#!/bin/sh\necho previous\n").unwrap();
+        let result = install_hook(&path);
+        let hook = fs::read_to_string(&path).unwrap();
+        let previous = fs::read_to_string(&backup).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+        assert_eq!(hook, "#!/bin/sh\necho current\n");
+        assert_eq!(previous, "#!/bin/sh\necho previous\n");
+    }
+}
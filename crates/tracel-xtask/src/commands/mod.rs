@@ -1,20 +1,27 @@
+pub mod bench;
 pub mod build;
 pub mod bump;
 pub mod check;
+pub mod clean;
 pub mod compile;
 pub mod coverage;
 pub mod dependencies;
 pub mod doc;
 pub mod fix;
+pub mod hooks;
 pub mod publish;
 pub mod test;
 pub mod validate;
 pub mod vulnerabilities;
 
 // use crate::declare_target;
+use std::path::Path;
+
 use clap::ValueEnum;
 use strum::{Display, EnumIter, EnumString};
 
+use crate::utils::{cargo::ensure_cargo_crate_is_installed, process::split_vector};
+
 pub const CARGO_NIGHTLY_MSG: &str = "You must use 'cargo +nightly' to run nightly checks.
 Install a nightly toolchain with 'rustup toolchain install nightly'.";
 pub const WARN_IGNORED_EXCLUDE_AND_ONLY_ARGS: &str =
@@ -33,3 +40,102 @@ pub enum Profile {
     Debug,
     Release,
 }
+
+/// Strategy used by cargo-hack to iterate over the features of each package.
+#[derive(EnumString, Display, Clone, Copy, PartialEq, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum HackMode {
+    /// Run the command once for each feature.
+    EachFeature,
+    /// Run the command once for each combination of features.
+    FeaturePowerset,
+}
+
+impl HackMode {
+    fn flag(&self) -> &'static str {
+        match self {
+            HackMode::EachFeature => "--each-feature",
+            HackMode::FeaturePowerset => "--feature-powerset",
+        }
+    }
+}
+
+/// Install cargo-hack if a hack mode is selected.
+pub(crate) fn ensure_hack_is_installed(hack: Option<HackMode>) -> anyhow::Result<()> {
+    if hack.is_some() {
+        ensure_cargo_crate_is_installed("cargo-hack", None, None, false)?;
+    }
+    Ok(())
+}
+
+/// Route the cargo arguments through cargo-hack when a hack mode is selected.
+/// The mode flag is inserted before the binary arguments following '--'.
+pub(crate) fn with_hack_mode<'a>(args: &[&'a str], hack: Option<HackMode>) -> Vec<&'a str> {
+    match hack {
+        Some(mode) => {
+            let (cargo_args, binary_args) = split_vector(args, "--");
+            let mut cmd_args = vec!["hack"];
+            cmd_args.extend(cargo_args);
+            cmd_args.push(mode.flag());
+            cmd_args.extend(binary_args);
+            cmd_args
+        }
+        None => args.to_vec(),
+    }
+}
+
+/// Return the typos arguments to use the given configuration file instead of the discovered one.
+/// Fail if the file does not exist.
+pub(crate) fn typos_config_args(config: Option<&Path>) -> anyhow::Result<Vec<String>> {
+    match config {
+        Some(path) if !path.is_file() => Err(anyhow::anyhow!(
+            "The typos configuration file {} does not exist.",
+            path.display()
+        )),
+        Some(path) => Ok(vec![
+            "--config".to_string(),
+            path.to_string_lossy().to_string(),
+        ]),
+        None => Ok(vec![]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::no_hack(None, &["build", "--workspace"], &["build", "--workspace"])]
+    #[case::each_feature(
+        Some(HackMode::EachFeature),
+        &["build", "--workspace"],
+        &["hack", "build", "--workspace", "--each-feature"]
+    )]
+    #[case::binary_args_kept_last(
+        Some(HackMode::FeaturePowerset),
+        &["clippy", "--no-deps", "--", "--deny", "warnings"],
+        &["hack", "clippy", "--no-deps", "--feature-powerset", "--", "--deny", "warnings"]
+    )]
+    fn test_with_hack_mode(
+        #[case] hack: Option<HackMode>,
+        #[case] args: &[&str],
+        #[case] expected: &[&str],
+    ) {
+        assert_eq!(with_hack_mode(args, hack), expected);
+    }
+
+    #[test]
+    fn test_typos_config_args() {
+        assert!(typos_config_args(None).unwrap().is_empty());
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        assert_eq!(
+            typos_config_args(Some(&manifest)).unwrap(),
+            [
+                "--config".to_string(),
+                manifest.to_string_lossy().to_string()
+            ]
+        );
+        assert!(typos_config_args(Some(Path::new("does/not/exist/_typos.toml"))).is_err());
+    }
+}
@@ -3,7 +3,9 @@ use std::{env, process::Command, str};
 use anyhow::{anyhow, Ok};
 
 use crate::{
-    endgroup, group,
+    endgroup,
+    errors::spawn_error,
+    group,
     utils::{cargo::parse_cargo_search_output, process::run_process},
 };
 
@@ -14,11 +16,23 @@ const CRATES_IO_API_TOKEN: &str = "CRATES_IO_API_TOKEN";
 pub struct PublishCmdArgs {
     /// The name of the crate to publish on crates.io
     name: String,
+    /// Only check the packaging of the crate with 'cargo publish --dry-run', nothing is uploaded
+    /// and no crates.io API token is required.
+    #[arg(long)]
+    dry_run: bool,
 }
 
 pub fn handle_command(args: PublishCmdArgs) -> anyhow::Result<()> {
     let crate_name = args.name;
 
+    if args.dry_run {
+        group!("Publishing crate '{}' (dry run)...", &crate_name);
+        publish_dry_run(&crate_name)?;
+        info!("Crate '{}' is ready to be published.", &crate_name);
+        endgroup!();
+        return Ok(());
+    }
+
     group!("Publishing crate '{}'...", &crate_name);
     // Retrieve local version for crate
     let local_version = local_version(&crate_name)?;
@@ -48,7 +62,7 @@ fn local_version(crate_name: &str) -> anyhow::Result<String> {
     let cargo_pkgid_output = Command::new("cargo")
         .args(["pkgid", "-p", crate_name])
         .output()
-        .map_err(|e| anyhow!("Failed to execute cargo pkgid: {}", e))?;
+        .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo pkgid"))?;
     // Convert cargo pkgid output into a str
     let cargo_pkgid_str = str::from_utf8(&cargo_pkgid_output.stdout)
         .expect("Failed to convert pkgid output into a str");
@@ -65,7 +79,7 @@ fn remote_version(crate_name: &str) -> anyhow::Result<Option<String>> {
     let cargo_search_output = Command::new("cargo")
         .args(["search", crate_name, "--limit", "1"])
         .output()
-        .map_err(|e| anyhow!("Failed to execute cargo search: {}", e))?;
+        .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo search"))?;
     // Cargo search returns an empty string in case of a crate not present on crates.io
     if !cargo_search_output.stdout.is_empty() {
         let output_str = str::from_utf8(&cargo_search_output.stdout).unwrap();
@@ -82,15 +96,19 @@ fn remote_version(crate_name: &str) -> anyhow::Result<Option<String>> {
     Ok(None)
 }
 
-fn publish(crate_name: String) -> anyhow::Result<()> {
-    // Perform dry-run to ensure everything is good for publishing
+fn publish_dry_run(crate_name: &str) -> anyhow::Result<()> {
     run_process(
         "cargo",
-        &["publish", "-p", &crate_name, "--dry-run"],
+        &["publish", "-p", crate_name, "--dry-run"],
         None,
         None,
-        &format!("Publish dry run failed for crate '{}'.", &crate_name),
-    )?;
+        &format!("Publish dry run failed for crate '{}'.", crate_name),
+    )
+}
+
+fn publish(crate_name: String) -> anyhow::Result<()> {
+    // Perform dry-run to ensure everything is good for publishing
+    publish_dry_run(&crate_name)?;
 
     let crates_io_token =
         env::var(CRATES_IO_API_TOKEN).expect("Failed to retrieve the crates.io API token");
@@ -99,7 +117,7 @@ fn publish(crate_name: String) -> anyhow::Result<()> {
         .env("CRATES_IO_API_TOKEN", crates_io_token.clone())
         .args(["publish", "-p", &crate_name, "--token", &crates_io_token])
         .status()
-        .map_err(|e| anyhow!("Failed to execute cargo publish: {}", e))?;
+        .map_err(|e| spawn_error("cargo", e, "Failed to execute cargo publish"))?;
     if !status.success() {
         return Err(anyhow!("Publish failed for crate '{}'.", &crate_name));
     }
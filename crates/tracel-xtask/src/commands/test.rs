@@ -1,62 +1,422 @@
-use anyhow::Result;
-use strum::IntoEnumIterator;
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use strum::{Display, EnumString, IntoEnumIterator};
 
 use crate::{
-    commands::WARN_IGNORED_ONLY_ARGS,
-    endgroup, group,
+    classify_error,
+    commands::{
+        ensure_hack_is_installed, with_hack_mode, CARGO_NIGHTLY_MSG, WARN_IGNORED_ONLY_ARGS,
+    },
+    endgroup, environment, group,
     utils::{
-        process::{run_process_for_package, run_process_for_workspace},
-        workspace::{get_workspace_members, WorkspaceMember, WorkspaceMemberType},
+        cargo::ensure_cargo_crate_is_installed,
+        junit::{enable_junit, take_junit_suites, write_junit_report},
+        process::{
+            discard_test_suites, enable_test_totals, failed_test_suites, reset_test_suite_totals,
+            run_process_for_package, run_process_for_workspace, take_test_totals, CargoLockArgs,
+        },
+        rustup::is_current_toolchain_nightly,
+        workspace::{
+            get_workspace_members, get_workspace_targets, WorkspaceMember, WorkspaceMemberType,
+            WorkspaceTarget,
+        },
+    },
+    Environment, XtaskError,
+};
+
+use super::{
+    coverage::{
+        clean_profraw_files, install_grcov, run_grcov, setup_coverage,
+        CleanCmdArgs as CoverageCleanCmdArgs, GenerateCmdArgs,
     },
+    doc::{DocCmdArgs, DocSubCommand},
+    Profile, Target,
 };
 
-use super::Target;
+/// Values above this limit for jobs and test threads are capped
+const MAX_THREADS: u16 = 1024;
 
 #[tracel_xtask_macros::declare_command_args(Target, TestSubCommand)]
 pub struct TestCmdArgs {}
 
+/// Backend used to execute the tests.
+#[derive(EnumString, Default, Display, Clone, Copy, PartialEq, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum TestRunner {
+    /// Run the tests with 'cargo test'.
+    #[default]
+    Cargo,
+    /// Run the tests with 'cargo nextest run'.
+    Nextest,
+}
+
+/// Run the tests in the environment selected with '--environment'.
 pub fn handle_command(args: TestCmdArgs) -> anyhow::Result<()> {
+    handle_command_in_env(args, environment())
+}
+
+/// Run the tests in the given environment.
+pub fn handle_command_in_env(args: TestCmdArgs, env: Environment) -> anyhow::Result<()> {
+    // the JSON output of the test harness is an unstable option
+    if args.junit.is_some() && !is_current_toolchain_nightly() {
+        return Err(anyhow!(
+            "'--junit' requires a nightly toolchain.\n{}",
+            CARGO_NIGHTLY_MSG
+        ));
+    }
+    enable_test_totals();
+    let junit = args.junit.clone();
+    if junit.is_some() {
+        enable_junit();
+    }
+    let coverage = args.coverage;
+    // the report is generated from the binaries of the profile the tests were built with
+    let profile = if args.release {
+        Profile::Release
+    } else {
+        Profile::Debug
+    };
+    if coverage {
+        group!("Coverage setup");
+        install_grcov()?;
+        // stale profraw files of previous runs would be merged into the report
+        clean_profraw_files(&CoverageCleanCmdArgs::default())?;
+        setup_coverage()?;
+        endgroup!();
+    }
+    let result = run_tests(args, env);
+    let totals = take_test_totals();
+    info!(
+        "Test summary: {} passed, {} failed, {} ignored",
+        totals.passed, totals.failed, totals.ignored
+    );
+    if let Some(path) = junit {
+        write_junit_report(&path, &take_junit_suites())?;
+    }
+    if coverage && result.is_ok() {
+        run_grcov(&GenerateCmdArgs {
+            profile,
+            ..GenerateCmdArgs::default()
+        })?;
+    }
+    result
+}
+
+fn run_tests(mut args: TestCmdArgs, env: Environment) -> anyhow::Result<()> {
+    check_environment(&env, args.force)?;
+    args.jobs = validate_thread_count("--compilation-jobs", args.jobs)?;
+    args.threads = validate_thread_count("--test-threads", args.threads)?;
+    parse_package_features(&args.package_features)?;
+    // clap already enforces it on the command line but the arguments can be built in code
+    if args.exact && args.test.is_empty() {
+        return Err(anyhow!("'--exact' requires at least one '--test' filter."));
+    }
+    ensure_hack_is_installed(args.hack)?;
+    if args.runner == TestRunner::Nextest {
+        if args.junit.is_some() {
+            return Err(anyhow!(
+                "'--junit' is not supported with the nextest runner, use its JUnit support instead."
+            ));
+        }
+        ensure_cargo_crate_is_installed("cargo-nextest", None, None, true)?;
+    }
     if args.target == Target::Workspace && !args.only.is_empty() {
         warn!("{}", WARN_IGNORED_ONLY_ARGS);
     }
-    match args.get_command() {
+    let result = match args.get_command() {
         TestSubCommand::Unit => run_unit(&args.target, &args),
         TestSubCommand::Integration => run_integration(&args.target, &args),
+        TestSubCommand::Doc => run_doc(&args),
+        // documentation tests are also executed by the doc command so they are only
+        // executed explicitly
         TestSubCommand::All => TestSubCommand::iter()
-            .filter(|c| *c != TestSubCommand::All)
+            .filter(|c| *c != TestSubCommand::All && *c != TestSubCommand::Doc)
             .try_for_each(|c| {
-                handle_command(TestCmdArgs {
-                    command: Some(c),
-                    target: args.target.clone(),
-                    exclude: args.exclude.clone(),
-                    only: args.only.clone(),
-                    threads: args.threads,
-                    jobs: args.jobs,
-                    features: args.features.clone(),
-                    no_default_features: args.no_default_features,
-                })
+                run_tests(
+                    TestCmdArgs {
+                        command: Some(c),
+                        ..args.clone()
+                    },
+                    env.clone(),
+                )
             }),
+    };
+    classify_error(result, XtaskError::TestsFailed, "Tests failed")
+}
+
+/// Tests may have side effects so they are not executed in production unless forced.
+fn check_environment(env: &Environment, force: bool) -> anyhow::Result<()> {
+    if *env == Environment::Production && !force {
+        return Err(anyhow!(
+            "Refusing to execute tests in {env} environment as they could alter production resources.\n\
+             Use the '--force' flag to execute them anyway."
+        ));
     }
+    Ok(())
 }
 
-fn push_optional_args(cmd_args: &mut Vec<String>, args: &TestCmdArgs) {
-    // cargo options
+/// Reject 0 which cargo and the test harness do not accept and cap unreasonable values.
+fn validate_thread_count(flag: &str, value: Option<u16>) -> anyhow::Result<Option<u16>> {
+    match value {
+        Some(0) => Err(anyhow!("'{flag}' must be greater than 0.")),
+        Some(count) if count > MAX_THREADS => {
+            warn!("'{flag}' value {count} is too high, capping it to {MAX_THREADS}.");
+            Ok(Some(MAX_THREADS))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Parse the repeatable '--package-features CRATE=FEATURE,FEATURE,...' arguments
+/// into a map of features to enable for each crate.
+fn parse_package_features(values: &[String]) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+    let mut package_features: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for value in values {
+        let (package, features) = value.split_once('=').ok_or_else(|| {
+            anyhow!(
+                "Invalid '--package-features' value '{value}', expected CRATE=FEATURE,FEATURE,..."
+            )
+        })?;
+        let package = package.trim();
+        let features: Vec<String> = features
+            .split(',')
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(|f| f.to_string())
+            .collect();
+        if package.is_empty() || features.is_empty() {
+            return Err(anyhow!(
+                "Invalid '--package-features' value '{value}', expected CRATE=FEATURE,FEATURE,..."
+            ));
+        }
+        package_features
+            .entry(package.to_string())
+            .or_default()
+            .extend(features);
+    }
+    Ok(package_features)
+}
+
+/// Push the cargo and test harness options.
+/// When PACKAGE is None the command targets the workspace and the per-crate features are
+/// passed using the 'crate/feature' syntax.
+fn push_optional_args(cmd_args: &mut Vec<String>, args: &TestCmdArgs, package: Option<&str>) {
+    // the commands are written for 'cargo test', nextest accepts the same cargo options
+    if args.runner == TestRunner::Nextest && cmd_args.first().is_some_and(|c| c == "test") {
+        cmd_args.splice(0..1, ["nextest".to_string(), "run".to_string()]);
+    }
+    // cargo options, nextest uses '--jobs' for the number of tests run in parallel
     if let Some(jobs) = &args.jobs {
-        cmd_args.extend(vec!["--jobs".to_string(), jobs.to_string()]);
+        let flag = match args.runner {
+            TestRunner::Cargo => "--jobs",
+            TestRunner::Nextest => "--build-jobs",
+        };
+        cmd_args.extend(vec![flag.to_string(), jobs.to_string()]);
     };
     if let Some(features) = &args.features {
         if !features.is_empty() {
             cmd_args.extend(vec!["--features".to_string(), features.join(",")]);
         }
     }
+    // already validated in handle_command
+    let package_features = parse_package_features(&args.package_features).unwrap_or_default();
+    let features: Vec<String> = match package {
+        Some(package) => package_features.get(package).cloned().unwrap_or_default(),
+        None => package_features
+            .iter()
+            .flat_map(|(package, features)| features.iter().map(move |f| format!("{package}/{f}")))
+            .collect(),
+    };
+    if !features.is_empty() {
+        cmd_args.extend(vec!["--features".to_string(), features.join(",")]);
+    }
     if args.no_default_features {
         cmd_args.push("--no-default-features".to_string());
     }
-    // test harness options
+    if args.all_features {
+        cmd_args.push("--all-features".to_string());
+    }
+    if args.release {
+        cmd_args.push("--release".to_string());
+    }
+    match args.runner {
+        TestRunner::Cargo => push_harness_args(cmd_args, args),
+        TestRunner::Nextest => push_nextest_args(cmd_args, args),
+    }
+    let lock_args = CargoLockArgs::new(args.locked, args.frozen, args.offline);
+    let with_lock_args = lock_args.apply(&cmd_args.iter().map(String::as_str).collect::<Vec<_>>());
+    *cmd_args = with_lock_args.into_iter().map(String::from).collect();
+}
+
+/// Push the test harness options.
+fn push_harness_args(cmd_args: &mut Vec<String>, args: &TestCmdArgs) {
     cmd_args.extend(vec!["--".to_string(), "--color=always".to_string()]);
     if let Some(threads) = &args.threads {
         cmd_args.extend(vec!["--test-threads".to_string(), threads.to_string()]);
     };
+    if args.no_capture {
+        cmd_args.push("--nocapture".to_string());
+    }
+    if args.ignored {
+        cmd_args.push("--ignored".to_string());
+    } else if args.include_ignored {
+        cmd_args.push("--include-ignored".to_string());
+    }
+    if args.exact {
+        cmd_args.push("--exact".to_string());
+    }
+    cmd_args.extend(args.test.iter().cloned());
+    if args.junit.is_some() {
+        cmd_args.extend(
+            [
+                "-Z",
+                "unstable-options",
+                "--format",
+                "json",
+                "--report-time",
+            ]
+            .map(String::from),
+        );
+    }
+    // the process runners keep everything after '--' at the end when injecting cargo arguments
+    cmd_args.extend(args.binary_args.iter().cloned());
+}
+
+/// Push the nextest equivalents of the test harness options.
+fn push_nextest_args(cmd_args: &mut Vec<String>, args: &TestCmdArgs) {
+    if let Some(threads) = &args.threads {
+        cmd_args.extend(vec!["--test-threads".to_string(), threads.to_string()]);
+    };
+    if args.no_capture {
+        cmd_args.push("--no-capture".to_string());
+    }
+    if args.ignored {
+        cmd_args.extend(vec!["--run-ignored".to_string(), "only".to_string()]);
+    } else if args.include_ignored {
+        cmd_args.extend(vec!["--run-ignored".to_string(), "all".to_string()]);
+    }
+    // nextest retries the failing tests by itself
+    if args.retries > 0 {
+        cmd_args.extend(vec!["--retries".to_string(), args.retries.to_string()]);
+    }
+    // nextest accepts the libtest filters and '--exact' after '--'
+    if !args.test.is_empty() || !args.binary_args.is_empty() {
+        cmd_args.push("--".to_string());
+        if args.exact {
+            cmd_args.push("--exact".to_string());
+        }
+        cmd_args.extend(args.test.iter().cloned());
+        cmd_args.extend(args.binary_args.iter().cloned());
+    }
+}
+
+/// Number of times the test processes are re-run by xtask when some tests fail.
+fn process_retries(args: &TestCmdArgs) -> u16 {
+    match args.runner {
+        TestRunner::Cargo => args.retries,
+        TestRunner::Nextest => 0,
+    }
+}
+
+/// Run the documentation tests of the selected target with the doc command.
+fn run_doc(args: &TestCmdArgs) -> Result<()> {
+    super::doc::handle_command(DocCmdArgs {
+        target: args.target.clone(),
+        exclude: args.exclude.clone(),
+        only: args.only.clone(),
+        locked: args.locked,
+        frozen: args.frozen,
+        offline: args.offline,
+        command: Some(DocSubCommand::Tests),
+        require_docs: false,
+        max_warnings: None,
+        deny_warnings: false,
+        output_dir: None,
+        open: false,
+        features: args.features.clone(),
+        no_default_features: args.no_default_features,
+        all_features: args.all_features,
+    })
+}
+
+/// Run the tests and re-run the test binaries with failed tests up to `--retries` times,
+/// the test binaries which passed are not executed again.
+/// Other failures like compilation errors are returned without retrying.
+fn run_with_retries(args: &TestCmdArgs, run: impl Fn() -> Result<()>) -> Result<()> {
+    let retries = process_retries(args);
+    reset_test_suite_totals();
+    let mut result = run();
+    for attempt in 1..=retries {
+        let Err(e) = &result else {
+            break;
+        };
+        let failed = failed_test_suites();
+        let commands = retry_commands(args, &failed, &get_workspace_targets());
+        if commands.is_empty() {
+            break;
+        }
+        warn!("{}", e);
+        info!("Retry {}/{}: {}", attempt, retries, failed.join(", "));
+        // only the results of the last run of the retried test binaries are reported
+        discard_test_suites(&failed);
+        let results: Vec<Result<()>> = commands
+            .iter()
+            .map(|(package, cmd_args)| {
+                run_process_for_package(
+                    "cargo",
+                    package,
+                    &with_hack_mode(
+                        &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
+                        args.hack,
+                    ),
+                    None,
+                    &[],
+                    &[],
+                    &format!("Failed to execute the retried tests of '{package}'"),
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        result = results.into_iter().find(Result::is_err).unwrap_or(Ok(()));
+    }
+    result
+}
+
+/// Return the cargo test commands re-running the FAILED test binaries, one per package.
+/// The test binary of a target is named after the target with '-' replaced by '_'.
+fn retry_commands(
+    args: &TestCmdArgs,
+    failed: &[String],
+    targets: &[WorkspaceTarget],
+) -> Vec<(String, Vec<String>)> {
+    let mut selections: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for target in targets
+        .iter()
+        .filter(|t| failed.contains(&t.name.replace('-', "_")))
+    {
+        let selection = match target.kind.as_str() {
+            "lib" => vec!["--lib".to_string()],
+            "bin" | "test" | "example" => vec![format!("--{}", target.kind), target.name.clone()],
+            _ => continue,
+        };
+        selections
+            .entry(target.package.as_str())
+            .or_default()
+            .extend(selection);
+    }
+    selections
+        .into_iter()
+        .map(|(package, selection)| {
+            let mut cmd_args = vec!["test".to_string(), "-p".to_string(), package.to_string()];
+            cmd_args.extend(selection);
+            cmd_args.extend(["--color".to_string(), "always".to_string()]);
+            push_optional_args(&mut cmd_args, args, Some(package));
+            (package.to_string(), cmd_args)
+        })
+        .collect()
 }
 
 pub fn run_unit(target: &Target, args: &TestCmdArgs) -> Result<()> {
@@ -75,18 +435,24 @@ pub fn run_unit(target: &Target, args: &TestCmdArgs) -> Result<()> {
             .into_iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
-            push_optional_args(&mut cmd_args, args);
+            push_optional_args(&mut cmd_args, args, None);
             // let cmd_args: Vec<&str> = cmd_args.iter().map(String::as_str).collect();
-            run_process_for_workspace(
-                "cargo",
-                &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
-                &args.exclude,
-                Some(r".*target/[^/]+/deps/([^-\s]+)"),
-                Some("Unit Tests"),
-                "Workspace Unit Tests failed",
-                Some("no library targets found"),
-                Some("No library found to test for in workspace."),
-            )?;
+            run_with_retries(args, || {
+                run_process_for_workspace(
+                    "cargo",
+                    &with_hack_mode(
+                        &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
+                        args.hack,
+                    ),
+                    None,
+                    &args.exclude,
+                    Some(r".*target/[^/]+/deps/([^-\s]+)"),
+                    Some("Unit Tests"),
+                    "Workspace Unit Tests failed",
+                    Some("no library targets found"),
+                    Some("No library found to test for in workspace."),
+                )
+            })?;
         }
         Target::Crates | Target::Examples => {
             let members = match target {
@@ -122,20 +488,26 @@ fn run_unit_test(member: &WorkspaceMember, args: &TestCmdArgs) -> Result<(), any
     .into_iter()
     .map(|s| s.to_string())
     .collect::<Vec<String>>();
-    push_optional_args(&mut cmd_args, args);
-    run_process_for_package(
-        "cargo",
-        &member.name,
-        &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
-        &args.exclude,
-        &args.only,
-        &format!("Failed to execute unit test for '{}'", &member.name),
-        Some("no library targets found"),
-        Some(&format!(
-            "No library found to test for in the crate '{}'.",
-            &member.name
-        )),
-    )?;
+    push_optional_args(&mut cmd_args, args, Some(&member.name));
+    run_with_retries(args, || {
+        run_process_for_package(
+            "cargo",
+            &member.name,
+            &with_hack_mode(
+                &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
+                args.hack,
+            ),
+            None,
+            &args.exclude,
+            &args.only,
+            &format!("Failed to execute unit test for '{}'", &member.name),
+            Some("no library targets found"),
+            Some(&format!(
+                "No library found to test for in the crate '{}'.",
+                &member.name
+            )),
+        )
+    })?;
     endgroup!();
     anyhow::Ok(())
 }
@@ -148,17 +520,23 @@ pub fn run_integration(target: &Target, args: &TestCmdArgs) -> anyhow::Result<()
                 .into_iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
-            push_optional_args(&mut cmd_args, args);
-            run_process_for_workspace(
-                "cargo",
-                &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
-                &args.exclude,
-                Some(r".*target/[^/]+/deps/([^-\s]+)"),
-                Some("Integration Tests"),
-                "Workspace Integration Tests failed",
-                Some("no test target matches pattern"),
-                Some("No tests found matching the pattern `test_*` in workspace."),
-            )?;
+            push_optional_args(&mut cmd_args, args, None);
+            run_with_retries(args, || {
+                run_process_for_workspace(
+                    "cargo",
+                    &with_hack_mode(
+                        &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
+                        args.hack,
+                    ),
+                    None,
+                    &args.exclude,
+                    Some(r".*target/[^/]+/deps/([^-\s]+)"),
+                    Some("Integration Tests"),
+                    "Workspace Integration Tests failed",
+                    Some("no test target matches pattern"),
+                    Some("No tests found matching the pattern `test_*` in workspace."),
+                )
+            })?;
         }
         Target::Crates | Target::Examples => {
             let members = match target {
@@ -194,20 +572,168 @@ fn run_integration_test(member: &WorkspaceMember, args: &TestCmdArgs) -> Result<
     .into_iter()
     .map(|s| s.to_string())
     .collect::<Vec<String>>();
-    push_optional_args(&mut cmd_args, args);
-    run_process_for_package(
-        "cargo",
-        &member.name,
-        &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
-        &args.exclude,
-        &args.only,
-        &format!("Failed to execute integration test for '{}'", &member.name),
-        Some("no test target matches pattern"),
-        Some(&format!(
-            "No tests found matching the pattern `test_*` for '{}'.",
-            &member.name
-        )),
-    )?;
+    push_optional_args(&mut cmd_args, args, Some(&member.name));
+    run_with_retries(args, || {
+        run_process_for_package(
+            "cargo",
+            &member.name,
+            &with_hack_mode(
+                &cmd_args.iter().map(String::as_str).collect::<Vec<&str>>(),
+                args.hack,
+            ),
+            None,
+            &args.exclude,
+            &args.only,
+            &format!("Failed to execute integration test for '{}'", &member.name),
+            Some("no test target matches pattern"),
+            Some(&format!(
+                "No tests found matching the pattern `test_*` for '{}'.",
+                &member.name
+            )),
+        )
+    })?;
     endgroup!();
     anyhow::Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(clap::Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: TestCmdArgs,
+    }
+
+    #[rstest]
+    #[case::cargo(
+        &["--compilation-jobs", "2", "--exact", "--test", "foo", "unit"],
+        &["test", "--jobs", "2", "--", "--color=always", "--exact", "foo"]
+    )]
+    #[case::nextest(
+        &["--runner", "nextest", "--compilation-jobs", "2", "--exact", "--test", "foo", "unit"],
+        &["nextest", "run", "--build-jobs", "2", "--", "--exact", "foo"]
+    )]
+    #[case::nextest_binary_args(
+        &["--runner", "nextest", "--test", "foo", "--binary-arg", "--show-output", "unit"],
+        &["nextest", "run", "--", "foo", "--show-output"]
+    )]
+    #[case::release(
+        &["--release", "unit"],
+        &["test", "--release", "--", "--color=always"]
+    )]
+    #[case::locked(
+        &["--locked", "--offline", "unit"],
+        &["test", "--locked", "--offline", "--", "--color=always"]
+    )]
+    #[case::nextest_locked(
+        &["--runner", "nextest", "--locked", "--test", "foo", "unit"],
+        &["nextest", "run", "--locked", "--", "foo"]
+    )]
+    fn test_push_optional_args(#[case] cli_args: &[&str], #[case] expected: &[&str]) {
+        use clap::Parser;
+        let cli = TestCli::try_parse_from(["xtask"].iter().chain(cli_args)).unwrap();
+        let mut cmd_args = vec!["test".to_string()];
+        push_optional_args(&mut cmd_args, &cli.args, None);
+        assert_eq!(cmd_args, expected);
+    }
+
+    fn target(package: &str, kind: &str, name: &str) -> WorkspaceTarget {
+        WorkspaceTarget {
+            package: package.to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case::lib(&["tracel_xtask"], &[("tracel-xtask", &["test", "-p", "tracel-xtask", "--lib", "--color", "always", "--", "--color=always"][..])])]
+    #[case::integration_tests(
+        &["bump", "test_publish"],
+        &[("tracel-xtask", &["test", "-p", "tracel-xtask", "--test", "test-publish", "--test", "bump", "--color", "always", "--", "--color=always"][..])]
+    )]
+    #[case::several_packages(
+        &["xtask", "demo"],
+        &[
+            ("demo", &["test", "-p", "demo", "--example", "demo", "--color", "always", "--", "--color=always"][..]),
+            ("xtask", &["test", "-p", "xtask", "--bin", "xtask", "--color", "always", "--", "--color=always"][..]),
+        ]
+    )]
+    #[case::unknown_binary(&["workspace"], &[])]
+    fn test_retry_commands(#[case] failed: &[&str], #[case] expected: &[(&str, &[&str])]) {
+        use clap::Parser;
+        let cli = TestCli::try_parse_from(["xtask", "unit"]).unwrap();
+        let targets = vec![
+            target("tracel-xtask", "lib", "tracel_xtask"),
+            target("tracel-xtask", "test", "test-publish"),
+            target("tracel-xtask", "test", "bump"),
+            target("tracel-xtask", "custom-build", "build-script-build"),
+            target("xtask", "bin", "xtask"),
+            target("demo", "example", "demo"),
+        ];
+        let failed: Vec<String> = failed.iter().map(|f| f.to_string()).collect();
+        let commands = retry_commands(&cli.args, &failed, &targets);
+        let expected: Vec<(String, Vec<String>)> = expected
+            .iter()
+            .map(|(package, args)| {
+                (
+                    package.to_string(),
+                    args.iter().map(|a| a.to_string()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(commands, expected);
+    }
+
+    #[rstest]
+    #[case::development(Environment::Development, false, true)]
+    #[case::staging(Environment::Staging, false, true)]
+    #[case::production(Environment::Production, false, false)]
+    #[case::production_forced(Environment::Production, true, true)]
+    fn test_check_environment(
+        #[case] env: Environment,
+        #[case] force: bool,
+        #[case] expected_ok: bool,
+    ) {
+        assert_eq!(check_environment(&env, force).is_ok(), expected_ok);
+    }
+
+    #[rstest]
+    #[case::single(&["a=f1"], Some(vec![("a", vec!["f1"])]))]
+    #[case::multiple_features(&["a=f1,f2"], Some(vec![("a", vec!["f1", "f2"])]))]
+    #[case::multiple_crates(&["a=f1", "b=f2"], Some(vec![("a", vec!["f1"]), ("b", vec!["f2"])]))]
+    #[case::repeated_crate(&["a=f1", "a=f2"], Some(vec![("a", vec!["f1", "f2"])]))]
+    #[case::whitespaces(&[" a = f1 , f2 "], Some(vec![("a", vec!["f1", "f2"])]))]
+    #[case::missing_equal(&["a"], None)]
+    #[case::missing_crate(&["=f1"], None)]
+    #[case::missing_features(&["a="], None)]
+    fn test_parse_package_features(
+        #[case] values: &[&str],
+        #[case] expected: Option<Vec<(&str, Vec<&str>)>>,
+    ) {
+        let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        let expected = expected.map(|e| {
+            e.into_iter()
+                .map(|(p, f)| (p.to_string(), f.into_iter().map(String::from).collect()))
+                .collect::<BTreeMap<String, Vec<String>>>()
+        });
+        assert_eq!(parse_package_features(&values).ok(), expected);
+    }
+
+    #[rstest]
+    #[case::none(None, Some(None))]
+    #[case::zero(Some(0), None)]
+    #[case::one(Some(1), Some(Some(1)))]
+    #[case::max(Some(MAX_THREADS), Some(Some(MAX_THREADS)))]
+    #[case::above_max(Some(MAX_THREADS + 1), Some(Some(MAX_THREADS)))]
+    #[case::u16_max(Some(u16::MAX), Some(Some(MAX_THREADS)))]
+    fn test_validate_thread_count(
+        #[case] value: Option<u16>,
+        #[case] expected: Option<Option<u16>>,
+    ) {
+        let result = validate_thread_count("--test-threads", value);
+        assert_eq!(result.ok(), expected);
+    }
+}
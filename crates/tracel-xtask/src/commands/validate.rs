@@ -1,46 +1,175 @@
+use clap::ValueEnum;
+use strum::{Display, EnumString};
+
+use crate::{
+    endgroup, environment, group,
+    utils::{
+        cargo::ensure_cargo_crate_is_installed,
+        process::{run_process_for_workspace, CargoLockArgs},
+    },
+    Environment,
+};
+
 use super::{
     check::{CheckCmdArgs, CheckSubCommand},
-    test::{TestCmdArgs, TestSubCommand},
+    doc::{DocCmdArgs, DocSubCommand},
+    test::{TestCmdArgs, TestRunner, TestSubCommand},
     Target,
 };
 
 #[tracel_xtask_macros::declare_command_args(None, None)]
 struct ValidateCmdArgs {}
 
+/// Set of steps run by the validate command.
+#[derive(EnumString, Default, Display, Clone, Copy, PartialEq, ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum ValidateProfile {
+    /// Format, lint and compile only.
+    Fast,
+    /// Audit, format, lint, typos, tests and documentation.
+    #[default]
+    Full,
+}
+
+/// Validate the code base in the environment selected with '--environment'.
 pub fn handle_command(args: ValidateCmdArgs) -> anyhow::Result<()> {
+    handle_command_in_env(args, environment())
+}
+
+/// Validate the code base in the given environment.
+pub fn handle_command_in_env(args: ValidateCmdArgs, env: Environment) -> anyhow::Result<()> {
     let target = Target::Workspace;
     let exclude = vec![];
     let only = vec![];
 
+    let checks = match args.profile {
+        ValidateProfile::Fast => vec![CheckSubCommand::Format, CheckSubCommand::Lint],
+        ValidateProfile::Full => vec![
+            CheckSubCommand::Audit,
+            CheckSubCommand::Format,
+            CheckSubCommand::Lint,
+            CheckSubCommand::Typos,
+        ],
+    };
+
     // checks
-    [
-        CheckSubCommand::Audit,
-        CheckSubCommand::Format,
-        CheckSubCommand::Lint,
-        CheckSubCommand::Typos,
-    ]
-    .iter()
-    .try_for_each(|c| {
+    checks.iter().try_for_each(|c| {
         super::check::handle_command(CheckCmdArgs {
             target: target.clone(),
             exclude: exclude.clone(),
             only: only.clone(),
+            locked: false,
+            frozen: false,
+            offline: false,
             command: Some(c.clone()),
             ignore_audit: args.ignore_audit,
+            baseline: None,
+            check_baseline: false,
+            api_baseline: None,
+            check_api_baseline: false,
+            compact_diagnostics: false,
+            strict: false,
+            all_features: false,
+            changed_since: None,
+            keep_going: false,
+            hack: None,
+            typos_config: None,
         })
     })?;
 
+    // feature combinations
+    if args.feature_powerset {
+        run_feature_powerset(&exclude, args.powerset_depth)?;
+    }
+
+    if args.profile == ValidateProfile::Fast {
+        return super::compile::run_compile(&target, &exclude, &only, CargoLockArgs::default());
+    }
+
     // tests
-    super::test::handle_command(TestCmdArgs {
-        target: target.clone(),
-        exclude: exclude.clone(),
-        only: only.clone(),
-        threads: None,
-        jobs: None,
-        command: Some(TestSubCommand::All),
-        features: None,
-        no_default_features: false,
-    })?;
+    super::test::handle_command_in_env(
+        TestCmdArgs {
+            target: target.clone(),
+            exclude: exclude.clone(),
+            only: only.clone(),
+            locked: false,
+            frozen: false,
+            offline: false,
+            threads: None,
+            jobs: None,
+            command: Some(TestSubCommand::All),
+            features: None,
+            no_default_features: false,
+            all_features: false,
+            hack: None,
+            retries: 0,
+            runner: TestRunner::Cargo,
+            junit: None,
+            package_features: vec![],
+            force: false,
+            no_capture: false,
+            ignored: false,
+            include_ignored: false,
+            test: vec![],
+            exact: false,
+            binary_args: vec![],
+            coverage: false,
+            release: false,
+        },
+        env,
+    )?;
+
+    // documentation
+    [DocSubCommand::Build, DocSubCommand::Tests]
+        .into_iter()
+        .try_for_each(|c| {
+            super::doc::handle_command(DocCmdArgs {
+                target: target.clone(),
+                exclude: exclude.clone(),
+                only: only.clone(),
+                locked: false,
+                frozen: false,
+                offline: false,
+                command: Some(c),
+                require_docs: false,
+                max_warnings: None,
+                deny_warnings: false,
+                output_dir: None,
+                open: false,
+                features: None,
+                no_default_features: false,
+                all_features: false,
+            })
+        })?;
+
+    Ok(())
+}
 
+/// Check every combination of features of the workspace members up to `depth` features
+/// at once so that incompatible feature combinations are caught at compile time.
+pub(crate) fn run_feature_powerset(excluded: &[String], depth: usize) -> anyhow::Result<()> {
+    ensure_cargo_crate_is_installed("cargo-hack", None, None, false)?;
+    group!("Feature Powerset (depth {})", depth);
+    let depth = depth.to_string();
+    run_process_for_workspace(
+        "cargo",
+        &[
+            "hack",
+            "check",
+            "--workspace",
+            "--feature-powerset",
+            "--depth",
+            &depth,
+            "--no-dev-deps",
+        ],
+        None,
+        excluded,
+        None,
+        None,
+        "Some feature combinations failed to compile",
+        None,
+        None,
+    )?;
+    endgroup!();
     Ok(())
 }
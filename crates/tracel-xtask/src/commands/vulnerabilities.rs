@@ -1,11 +1,21 @@
 use anyhow::{anyhow, Ok};
-use std::process::Command as StdCommand;
+use regex::Regex;
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Command as StdCommand, Stdio},
+    sync::OnceLock,
+};
 
 use crate::{
     commands::CARGO_NIGHTLY_MSG,
-    endgroup, group,
+    endgroup,
+    errors::spawn_error,
+    group,
     utils::{
+        browser,
         cargo::ensure_cargo_crate_is_installed,
+        git::display_relative_to_root,
         process::run_process,
         rustup::{
             is_current_toolchain_nightly, rustup_add_component, rustup_get_installed_targets,
@@ -17,39 +27,75 @@ use crate::{
 pub struct VulnerabilitiesCmdArgs {}
 
 pub fn handle_command(args: VulnerabilitiesCmdArgs) -> anyhow::Result<()> {
-    args.get_command().run()
+    let mut reports = vec![];
+    let result = args.get_command().run(&mut reports);
+    if let Some(path) = &args.report {
+        write_html_report(path, &reports)?;
+        info!(
+            "Vulnerabilities report written to {}",
+            display_relative_to_root(path)
+        );
+        if args.open {
+            browser::open(&path.to_string_lossy())?;
+        }
+    }
+    result
 }
 
 impl VulnerabilitiesSubCommand {
-    pub(crate) fn run(&self) -> anyhow::Result<()> {
+    pub(crate) fn run(&self, reports: &mut Vec<CheckReport>) -> anyhow::Result<()> {
         match self {
-            Self::NightlyChecks => run_cargo_careful(),
-            Self::AddressSanitizer => Sanitizer::Address.run_tests(),
-            Self::ControlFlowIntegrity => Sanitizer::CFI.run_tests(),
-            Self::HWAddressSanitizer => Sanitizer::HWAddress.run_tests(),
-            Self::KernelControlFlowIntegrity => Sanitizer::KCFI.run_tests(),
-            Self::LeakSanitizer => Sanitizer::Leak.run_tests(),
-            Self::MemorySanitizer => Sanitizer::Memory.run_tests(),
-            Self::MemTagSanitizer => Sanitizer::MemTag.run_tests(),
-            Self::SafeStack => Sanitizer::SafeStack.run_tests(),
-            Self::ShadowCallStack => Sanitizer::ShadowCallStack.run_tests(),
-            Self::ThreadSanitizer => Sanitizer::Thread.run_tests(),
+            Self::NightlyChecks => run_cargo_careful(reports),
+            Self::AddressSanitizer => Sanitizer::Address.run_tests(reports),
+            Self::ControlFlowIntegrity => Sanitizer::CFI.run_tests(reports),
+            Self::HWAddressSanitizer => Sanitizer::HWAddress.run_tests(reports),
+            Self::KernelControlFlowIntegrity => Sanitizer::KCFI.run_tests(reports),
+            Self::LeakSanitizer => Sanitizer::Leak.run_tests(reports),
+            Self::MemorySanitizer => Sanitizer::Memory.run_tests(reports),
+            Self::MemTagSanitizer => Sanitizer::MemTag.run_tests(reports),
+            Self::SafeStack => Sanitizer::SafeStack.run_tests(reports),
+            Self::ShadowCallStack => Sanitizer::ShadowCallStack.run_tests(reports),
+            Self::ThreadSanitizer => Sanitizer::Thread.run_tests(reports),
             Self::All => {
                 // TODO automatically run all checks supported by the default toolchain of the host
                 // For now run all those supported by X8664UnknownLinuxGnu
-                run_cargo_careful()?;
-                Sanitizer::Address.run_tests()?;
-                Sanitizer::Leak.run_tests()?;
-                Sanitizer::Memory.run_tests()?;
-                Sanitizer::SafeStack.run_tests()?;
-                Sanitizer::Thread.run_tests()
+                run_cargo_careful(reports)?;
+                Sanitizer::Address.run_tests(reports)?;
+                Sanitizer::Leak.run_tests(reports)?;
+                Sanitizer::Memory.run_tests(reports)?;
+                Sanitizer::SafeStack.run_tests(reports)?;
+                Sanitizer::Thread.run_tests(reports)
             }
         }
     }
 }
 
+// Outcome of a vulnerability check used to render the report
+pub(crate) struct CheckReport {
+    name: String,
+    status: CheckStatus,
+    findings: Vec<String>,
+}
+
+pub(crate) enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped(String),
+}
+
+impl CheckReport {
+    fn new(name: String, status: CheckStatus) -> Self {
+        Self {
+            name,
+            status,
+            findings: vec![],
+        }
+    }
+}
+
 /// Run cargo-careful
-fn run_cargo_careful() -> anyhow::Result<()> {
+fn run_cargo_careful(reports: &mut Vec<CheckReport>) -> anyhow::Result<()> {
+    let name = "cargo-careful".to_string();
     if is_current_toolchain_nightly() {
         ensure_cargo_crate_is_installed("cargo-careful", None, None, false)?;
         rustup_add_component("rust-src")?;
@@ -65,16 +111,26 @@ fn run_cargo_careful() -> anyhow::Result<()> {
         endgroup!();
         // Run cargo careful
         group!("Cargo: run careful checks");
-        run_process(
+        let result = run_process(
             "cargo",
             &["careful", "test"],
             None,
             None,
             "Cargo careful test has errors.",
-        )?;
+        );
+        let status = match result {
+            std::result::Result::Ok(_) => CheckStatus::Passed,
+            Err(_) => CheckStatus::Failed,
+        };
+        reports.push(CheckReport::new(name, status));
+        result?;
         endgroup!();
     } else {
         error!("{}", CARGO_NIGHTLY_MSG);
+        reports.push(CheckReport::new(
+            name,
+            CheckStatus::Skipped("A nightly toolchain is required.".to_string()),
+        ));
     }
     Ok(())
 }
@@ -115,7 +171,7 @@ impl std::fmt::Display for Sanitizer {
 impl Sanitizer {
     const DEFAULT_RUSTFLAGS: &'static str = "-Copt-level=3";
 
-    fn run_tests(&self) -> anyhow::Result<()> {
+    fn run_tests(&self, reports: &mut Vec<CheckReport>) -> anyhow::Result<()> {
         if is_current_toolchain_nightly() {
             group!("Sanitizer: {}", self.to_string());
             let retriever = RustupTargetRetriever;
@@ -131,20 +187,54 @@ impl Sanitizer {
                 let features = self.cargo_features();
                 let mut args = vec!["test", "--", "--color=always", "--no-capture"];
                 args.extend(features);
-                let status = StdCommand::new("cargo")
+                let mut child = StdCommand::new("cargo")
                     .args(&args)
                     .envs(envs)
-                    .status()
-                    .map_err(|e| anyhow!("Failed to execute instrumentalized test: {}", e))?;
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| {
+                        spawn_error("cargo", e, "Failed to execute instrumentalized test")
+                    })?;
+                // sanitizers report their findings on stderr
+                let mut findings = vec![];
+                if let Some(stderr) = child.stderr.take() {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        eprintln!("{}", line);
+                        if is_sanitizer_finding(&line) {
+                            findings.push(line);
+                        }
+                    }
+                }
+                let status = child
+                    .wait()
+                    .map_err(|e| anyhow!("Failed to wait for instrumentalized test: {}", e))?;
+                let mut report = CheckReport::new(
+                    self.to_string(),
+                    if status.success() {
+                        CheckStatus::Passed
+                    } else {
+                        CheckStatus::Failed
+                    },
+                );
+                report.findings = findings;
+                reports.push(report);
                 if !status.success() {
                     return Err(anyhow!("Sanitizer found issues!"));
                 }
             } else {
                 info!("No supported target found for this sanitizer.");
+                reports.push(CheckReport::new(
+                    self.to_string(),
+                    CheckStatus::Skipped("No supported target found.".to_string()),
+                ));
             }
             endgroup!();
         } else {
             error!("{}", CARGO_NIGHTLY_MSG);
+            reports.push(CheckReport::new(
+                self.to_string(),
+                CheckStatus::Skipped("A nightly toolchain is required.".to_string()),
+            ));
         }
         Ok(())
     }
@@ -227,6 +317,89 @@ impl Sanitizer {
     }
 }
 
+// Returns true if the line is a sanitizer report line, for instance:
+// ==1234==ERROR: AddressSanitizer: heap-use-after-free on address 0x...
+// SUMMARY: AddressSanitizer: heap-use-after-free src/main.rs:5:20 in main
+fn is_sanitizer_finding(line: &str) -> bool {
+    static FINDING_RX: OnceLock<Regex> = OnceLock::new();
+    let re = FINDING_RX.get_or_init(|| {
+        Regex::new(r"^(==\d+==(ERROR|WARNING): |WARNING: ThreadSanitizer: |SUMMARY: )")
+            .expect("should compile regex")
+    });
+    re.is_match(line.trim_start())
+}
+
+fn write_html_report(path: &Path, reports: &[CheckReport]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render_html_report(reports))
+        .map_err(|e| anyhow!("Failed to write report to {}: {}", path.display(), e))
+}
+
+fn render_html_report(reports: &[CheckReport]) -> String {
+    let mut rows = String::new();
+    for report in reports {
+        let (class, status) = match &report.status {
+            CheckStatus::Passed => ("passed", "Passed".to_string()),
+            CheckStatus::Failed => ("failed", "Failed".to_string()),
+            CheckStatus::Skipped(reason) => ("skipped", format!("Skipped ({})", reason)),
+        };
+        let findings = if report.findings.is_empty() {
+            "-".to_string()
+        } else {
+            format!(
+                "<pre>{}</pre>",
+                report
+                    .findings
+                    .iter()
+                    .map(|f| escape_html(f))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        };
+        rows.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            class,
+            escape_html(&report.name),
+            escape_html(&status),
+            findings
+        ));
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Vulnerabilities Report</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+td, th {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}
+tr.passed td:nth-child(2) {{ color: green; }}
+tr.failed td:nth-child(2) {{ color: red; }}
+tr.skipped td:nth-child(2) {{ color: gray; }}
+</style>
+</head>
+<body>
+<h1>Vulnerabilities Report</h1>
+<table>
+<tr><th>Check</th><th>Status</th><th>Findings</th></tr>
+{}</table>
+</body>
+</html>
+"#,
+        rows
+    )
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 // Constants for target names
 const AARCH64_APPLE_DARWIN: &str = "aarch64-apple-darwin";
 const AARCH64_LINUX_ANDROID: &str = "aarch64-linux-android";
@@ -338,6 +511,32 @@ mod tests {
         assert_eq!(sanitizer.is_target_supported(&mock_retriever), expected);
     }
 
+    #[rstest]
+    #[case::address_error(
+        "==1234==ERROR: AddressSanitizer: heap-use-after-free on address 0x1",
+        true
+    )]
+    #[case::leak_warning("==42==WARNING: LeakSanitizer: detected memory leaks", true)]
+    #[case::thread_warning("WARNING: ThreadSanitizer: data race (pid=8)", true)]
+    #[case::summary(
+        "SUMMARY: AddressSanitizer: heap-use-after-free src/main.rs:5:20 in main",
+        true
+    )]
+    #[case::test_output("test tests::my_test ... ok", false)]
+    #[case::empty("", false)]
+    fn test_is_sanitizer_finding(#[case] line: &str, #[case] expected: bool) {
+        assert_eq!(is_sanitizer_finding(line), expected);
+    }
+
+    #[test]
+    fn test_render_html_report_escapes_findings() {
+        let mut report = CheckReport::new("AddressSanitizer".to_string(), CheckStatus::Failed);
+        report.findings.push("SUMMARY: <unknown> & co".to_string());
+        let html = render_html_report(&[report]);
+        assert!(html.contains("SUMMARY: &lt;unknown&gt; &amp; co"));
+        assert!(html.contains("<tr class=\"failed\">"));
+    }
+
     #[test]
     fn test_consistency_of_fmt_and_from_str_strings() {
         let variants = vec![
@@ -0,0 +1,172 @@
+use std::io;
+
+/// Categorized errors of xtask commands, each category has its own process exit code
+/// so that CI pipelines can branch on the kind of failure.
+#[derive(Debug)]
+pub enum XtaskError {
+    /// A check found issues.
+    CheckFailed(String),
+    /// Some tests failed.
+    TestsFailed(String),
+    /// A required tool is not installed and could not be installed.
+    ToolNotInstalled(String),
+    /// A required tool could not be found in PATH, the hint tells how to install it.
+    ToolMissing { name: String, hint: String },
+}
+
+impl XtaskError {
+    /// Exit code for unclassified errors.
+    pub const GENERIC_EXIT_CODE: u8 = 1;
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            XtaskError::CheckFailed(_) => 2,
+            XtaskError::TestsFailed(_) => 3,
+            XtaskError::ToolNotInstalled(_) => 4,
+            XtaskError::ToolMissing { .. } => 5,
+        }
+    }
+
+    /// Return a [XtaskError::ToolMissing] error for the tool NAME with the given install HINT.
+    pub fn tool_missing(name: &str, hint: &str) -> Self {
+        XtaskError::ToolMissing {
+            name: name.to_string(),
+            hint: hint.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for XtaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            XtaskError::CheckFailed(msg)
+            | XtaskError::TestsFailed(msg)
+            | XtaskError::ToolNotInstalled(msg) => write!(f, "{}", msg),
+            XtaskError::ToolMissing { name, hint } => write!(
+                f,
+                "'{}' is required but could not be found in PATH.\n{}",
+                name, hint
+            ),
+        }
+    }
+}
+
+impl std::error::Error for XtaskError {}
+
+/// Return the default install hint of the well-known tool NAME.
+pub(crate) fn default_install_hint(name: &str) -> String {
+    match name {
+        "cargo" | "rustc" | "rustup" => {
+            "Install the Rust toolchain with rustup, see https://rustup.rs.".to_string()
+        }
+        "git" => "Install git, see https://git-scm.com/downloads.".to_string(),
+        _ => format!(
+            "Install '{}' and make sure that it is available in PATH.",
+            name
+        ),
+    }
+}
+
+/// Convert the error returned when spawning the process NAME, a binary that cannot be found
+/// is reported as [XtaskError::ToolMissing] and any other error is prefixed with CONTEXT.
+pub(crate) fn spawn_error(name: &str, error: io::Error, context: &str) -> anyhow::Error {
+    if error.kind() == io::ErrorKind::NotFound {
+        XtaskError::tool_missing(name, &default_install_hint(name)).into()
+    } else {
+        anyhow::anyhow!("{}: {}", context, error)
+    }
+}
+
+/// Error of a process which ran but did not succeed, see [classify_error].
+#[derive(Debug)]
+pub(crate) struct ProcessFailed(String);
+
+impl std::fmt::Display for ProcessFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ProcessFailed {}
+
+/// Return the error of a process which exited unsuccessfully with the given MSG.
+pub(crate) fn process_failed(msg: &str) -> anyhow::Error {
+    ProcessFailed(msg.to_string()).into()
+}
+
+/// Classify the failure of a process with the given CATEGORY, the original error is kept as
+/// its source. Other errors and the errors which have already been classified are unchanged.
+pub(crate) fn classify_error<T>(
+    result: anyhow::Result<T>,
+    category: fn(String) -> XtaskError,
+    msg: &str,
+) -> anyhow::Result<T> {
+    result.map_err(|e| {
+        let is_process_failure = e.chain().any(|c| c.is::<ProcessFailed>());
+        if e.is::<XtaskError>() || !is_process_failure {
+            e
+        } else {
+            e.context(category(msg.to_string()))
+        }
+    })
+}
+
+/// Print the error if any and return the corresponding process exit code.
+/// Errors that are not an [XtaskError] exit with [XtaskError::GENERIC_EXIT_CODE].
+pub fn to_exit_code(result: anyhow::Result<()>) -> std::process::ExitCode {
+    match result {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            let code = e
+                .downcast_ref::<XtaskError>()
+                .map_or(XtaskError::GENERIC_EXIT_CODE, |e| e.exit_code());
+            std::process::ExitCode::from(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::not_found(io::ErrorKind::NotFound, Some(5))]
+    #[case::permission_denied(io::ErrorKind::PermissionDenied, None)]
+    fn test_spawn_error(#[case] kind: io::ErrorKind, #[case] expected: Option<u8>) {
+        let error = spawn_error("git", io::Error::from(kind), "Failed to execute git");
+        assert_eq!(
+            error.downcast_ref::<XtaskError>().map(|e| e.exit_code()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::process_failure(process_failed("cargo test failed"), 3)]
+    #[case::classified(XtaskError::CheckFailed("clippy failed".to_string()).into(), 2)]
+    #[case::io(io::Error::from(io::ErrorKind::PermissionDenied).into(), 1)]
+    #[case::other(anyhow::anyhow!("'--exact' requires a filter"), 1)]
+    fn test_classify_error(#[case] error: anyhow::Error, #[case] expected: u8) {
+        let message = error.to_string();
+        let error =
+            classify_error::<()>(Err(error), XtaskError::TestsFailed, "Tests failed").unwrap_err();
+        assert_eq!(
+            error
+                .downcast_ref::<XtaskError>()
+                .map_or(XtaskError::GENERIC_EXIT_CODE, |e| e.exit_code()),
+            expected
+        );
+        // the original error is kept in the chain
+        assert!(error.chain().any(|c| c.to_string() == message));
+    }
+
+    #[test]
+    fn test_tool_missing_message() {
+        let error = XtaskError::tool_missing("git", &default_install_hint("git"));
+        assert_eq!(
+            error.to_string(),
+            "'git' is required but could not be found in PATH.\nInstall git, see https://git-scm.com/downloads."
+        );
+    }
+}
@@ -1,4 +1,8 @@
+// allow the macros to refer to the types of this crate with absolute paths
+extern crate self as tracel_xtask;
+
 pub mod commands;
+pub mod errors;
 pub mod logging;
 pub mod utils;
 mod versions;
@@ -10,6 +14,7 @@ pub mod prelude {
     pub use derive_more;
     pub use env_logger;
     pub use rand;
+    pub use semver;
     pub use serde_json;
     pub use tracing_subscriber;
 
@@ -22,11 +27,13 @@ pub mod prelude {
     }
 
     pub use crate::commands as base_commands;
+    pub use crate::commands::bench::BenchCmdArgs;
     pub use crate::commands::build::BuildCmdArgs;
     pub use crate::commands::bump::BumpCmdArgs;
     pub use crate::commands::bump::BumpSubCommand;
     pub use crate::commands::check::CheckCmdArgs;
     pub use crate::commands::check::CheckSubCommand;
+    pub use crate::commands::clean::CleanCmdArgs;
     pub use crate::commands::compile::CompileCmdArgs;
     pub use crate::commands::coverage::CoverageCmdArgs;
     pub use crate::commands::dependencies::DependenciesCmdArgs;
@@ -35,42 +42,66 @@ pub mod prelude {
     pub use crate::commands::doc::DocSubCommand;
     pub use crate::commands::fix::FixCmdArgs;
     pub use crate::commands::fix::FixSubCommand;
+    pub use crate::commands::hooks::InstallHooksCmdArgs;
     pub use crate::commands::publish::PublishCmdArgs;
     pub use crate::commands::test::TestCmdArgs;
+    pub use crate::commands::test::TestRunner;
     pub use crate::commands::test::TestSubCommand;
     pub use crate::commands::validate::ValidateCmdArgs;
+    pub use crate::commands::validate::ValidateProfile;
     pub use crate::commands::vulnerabilities::VulnerabilitiesCmdArgs;
     pub use crate::commands::vulnerabilities::VulnerabilitiesSubCommand;
     pub use crate::commands::Target;
     pub use crate::endgroup;
+    pub use crate::environment;
     pub use crate::group;
     pub use crate::group_info;
     pub use crate::init_xtask;
+    pub use crate::to_exit_code;
+    pub use crate::utils::browser;
     pub use crate::utils::cargo::ensure_cargo_crate_is_installed;
+    pub use crate::utils::ci;
     pub use crate::utils::helpers;
+    pub use crate::utils::output::output_mode;
+    pub use crate::utils::output::print_result;
+    pub use crate::utils::output::CommandResult;
+    pub use crate::utils::output::OutputMode;
     pub use crate::utils::process::random_port;
     pub use crate::utils::process::run_process;
     pub use crate::utils::process::run_process_for_package;
     pub use crate::utils::process::run_process_for_workspace;
     pub use crate::utils::prompt::ask_once;
+    pub use crate::utils::prompt::confirm;
     pub use crate::utils::rustup::is_current_toolchain_nightly;
     pub use crate::utils::rustup::rustup_add_component;
     pub use crate::utils::rustup::rustup_add_target;
     pub use crate::utils::rustup::rustup_get_installed_targets;
     pub use crate::utils::time::format_duration;
+    pub use crate::utils::tool::ensure_tool_available;
+    pub use crate::utils::tool::ensure_tool_version;
     pub use crate::Environment;
     pub use crate::ExecutionEnvironment;
     pub use crate::XtaskArgs;
+    pub use crate::XtaskError;
 }
 
-use crate::logging::init_logger;
+use crate::commands::coverage::setup_coverage;
+use crate::logging::{init_events, init_logger, set_grouping};
+use crate::utils::output::{print_result, set_output_mode, CommandResult, OutputMode};
+use crate::utils::process::set_cargo_extra_args;
+use crate::utils::prompt::set_assume_yes;
+use crate::utils::tool::{find_tool, tool_version};
 
 // does not re-export strum has it is incompatible with strum macros expansions
+use std::sync::OnceLock;
 use strum::{Display, EnumIter, EnumString};
 
 #[macro_use]
 extern crate log;
 
+pub(crate) use crate::errors::classify_error;
+pub use crate::errors::{to_exit_code, XtaskError};
+
 #[derive(EnumString, EnumIter, Default, Display, Clone, PartialEq, clap::ValueEnum)]
 #[strum(serialize_all = "lowercase")]
 pub enum Environment {
@@ -83,6 +114,14 @@ pub enum Environment {
     Production,
 }
 
+/// Environment selected with '--environment', see [environment].
+static ENVIRONMENT: OnceLock<Environment> = OnceLock::new();
+
+/// Return the environment selected with '--environment', the default one before [init_xtask].
+pub fn environment() -> Environment {
+    ENVIRONMENT.get().cloned().unwrap_or_default()
+}
+
 #[derive(EnumString, EnumIter, Default, Display, Clone, PartialEq, clap::ValueEnum)]
 #[strum(serialize_all = "lowercase")]
 pub enum ExecutionEnvironment {
@@ -108,16 +147,68 @@ pub struct XtaskArgs<C: clap::Subcommand> {
     /// Set execution environment (for commands that support it).
     #[arg(short = 'E', long, default_value_t = ExecutionEnvironment::default())]
     pub execution_environment: ExecutionEnvironment,
+    /// Append JSON Lines progress events to the given file.
+    #[arg(long, value_name = "PATH")]
+    pub events: Option<std::path::PathBuf>,
+    /// Extra argument appended to the cargo invocations of the commands, can be repeated.
+    /// Arguments are passed as is without any validation.
+    #[arg(
+        long = "cargo-arg",
+        value_name = "ARG",
+        allow_hyphen_values = true,
+        required = false
+    )]
+    pub cargo_args: Vec<String>,
+    /// Split the output of the commands into log groups, this is the default in CI.
+    #[arg(long, conflicts_with = "no_group")]
+    pub group: bool,
+    /// Pass the output of the commands through without splitting it into log groups, this is the default locally.
+    #[arg(long)]
+    pub no_group: bool,
+    /// Answer yes to all the confirmation prompts.
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+    /// Output mode of the command results.
+    #[arg(long, value_name = "MODE", default_value_t = OutputMode::default())]
+    pub output: OutputMode,
+    /// List the supported environments and exit.
+    #[arg(long)]
+    pub list_environments: bool,
+    /// Print the version of xtask and of the detected tools for bug reports and exit.
+    #[arg(long)]
+    pub versions: bool,
     #[command(subcommand)]
     pub command: C,
 }
 
 pub fn init_xtask<C: clap::Subcommand>() -> anyhow::Result<XtaskArgs<C>> {
     init_logger().init();
-    let args = <XtaskArgs<C> as clap::Parser>::parse();
+    let args = parse_args::<C>();
 
     group_info!("Execution environment: {}", args.execution_environment);
 
+    if let Some(path) = &args.events {
+        init_events(path)?;
+    }
+
+    if args.group || args.no_group {
+        set_grouping(args.group);
+    }
+
+    if args.yes {
+        set_assume_yes(true);
+    }
+
+    set_output_mode(args.output)?;
+    ENVIRONMENT
+        .set(args.environment.clone())
+        .map_err(|_| anyhow::anyhow!("Environment is already set"))?;
+
+    if !args.cargo_args.is_empty() {
+        group_info!("Extra cargo arguments: {}", args.cargo_args.join(" "));
+        set_cargo_extra_args(args.cargo_args.clone())?;
+    }
+
     // initialize code coverage
     if args.enable_coverage {
         group_info!("Enabling coverage support...");
@@ -127,10 +218,128 @@ pub fn init_xtask<C: clap::Subcommand>() -> anyhow::Result<XtaskArgs<C>> {
     Ok(args)
 }
 
-fn setup_coverage() -> anyhow::Result<()> {
-    unsafe {
-        std::env::set_var("RUSTFLAGS", "-Cinstrument-coverage");
-        std::env::set_var("LLVM_PROFILE_FILE", "burn-%p-%m.profraw");
+fn parse_args<C: clap::Subcommand>() -> XtaskArgs<C> {
+    use clap::{CommandFactory, FromArgMatches};
+    // the subcommand is optional only to support the exclusive --list-environments flag
+    let mut cmd = <XtaskArgs<C> as CommandFactory>::command().subcommand_required(false);
+    let matches = cmd.get_matches_mut();
+    if matches.get_flag("list_environments") {
+        let mode = matches
+            .get_one::<OutputMode>("output")
+            .copied()
+            .unwrap_or_default();
+        print_result(&EnvironmentList, mode);
+        std::process::exit(0);
+    }
+    if matches.get_flag("versions") {
+        let mode = matches
+            .get_one::<OutputMode>("output")
+            .copied()
+            .unwrap_or_default();
+        print_result(&VersionReport::detect(), mode);
+        std::process::exit(0);
+    }
+    if matches.subcommand().is_none() {
+        cmd.error(
+            clap::error::ErrorKind::MissingSubcommand,
+            "a subcommand is required but one was not provided",
+        )
+        .exit();
+    }
+    <XtaskArgs<C> as FromArgMatches>::from_arg_matches(&matches)
+        .unwrap_or_else(|e| e.format(&mut cmd).exit())
+}
+
+/// Tools whose version is reported by '--versions' when they are installed.
+const REPORTED_TOOLS: &[&str] = &[
+    "cargo",
+    "rustc",
+    "rustup",
+    "git",
+    "aws",
+    "docker",
+    "terraform",
+];
+
+/// Version of xtask and of the detected tools, see '--versions'.
+struct VersionReport {
+    /// Name and version of the tools found in PATH, None if the version cannot be determined.
+    tools: Vec<(String, Option<String>)>,
+}
+
+impl VersionReport {
+    fn detect() -> Self {
+        let tools = REPORTED_TOOLS
+            .iter()
+            .filter(|name| find_tool(name).is_some())
+            .map(|name| (name.to_string(), tool_version(name).map(|v| v.to_string())))
+            .collect();
+        Self { tools }
+    }
+}
+
+impl CommandResult for VersionReport {
+    fn to_human(&self) -> String {
+        let mut lines = vec![format!("tracel-xtask {}", env!("CARGO_PKG_VERSION"))];
+        for (name, version) in &self.tools {
+            lines.push(format!(
+                "  {:<12} {}",
+                name,
+                version.as_deref().unwrap_or("unknown version")
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let tools: serde_json::Map<String, serde_json::Value> = self
+            .tools
+            .iter()
+            .map(|(name, version)| (name.clone(), serde_json::json!(version)))
+            .collect();
+        serde_json::json!({ "tracel-xtask": env!("CARGO_PKG_VERSION"), "tools": tools })
+    }
+}
+
+/// Supported environments, see '--list-environments'.
+struct EnvironmentList;
+
+impl EnvironmentList {
+    /// Return the name, the description and whether it is the default of each environment.
+    fn entries() -> Vec<(String, String, bool)> {
+        use clap::ValueEnum;
+        Environment::value_variants()
+            .iter()
+            .map(|env| {
+                let value = env
+                    .to_possible_value()
+                    .expect("environment should not be skipped");
+                (
+                    value.get_name().to_string(),
+                    value.get_help().map(|h| h.to_string()).unwrap_or_default(),
+                    *env == Environment::default(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl CommandResult for EnvironmentList {
+    fn to_human(&self) -> String {
+        let mut lines = vec!["Supported environments (set with -e, --environment):".to_string()];
+        for (name, help, default) in Self::entries() {
+            let default = if default { " [default]" } else { "" };
+            lines.push(format!("  {:<12} {}{}", name, help, default));
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        Self::entries()
+            .into_iter()
+            .map(|(name, help, default)| {
+                serde_json::json!({ "name": name, "description": help, "default": default })
+            })
+            .collect()
     }
-    Ok(())
 }
@@ -1,4 +1,15 @@
-use std::io::Write;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::ci::is_ci;
+
+/// Sink of the JSON Lines progress events, only set when the '--events' option is passed.
+static EVENTS_SINK: OnceLock<Mutex<File>> = OnceLock::new();
 
 /// Initialise and create a `env_logger::Builder` which follows the
 /// GitHub Actions logging syntax when running on CI.
@@ -11,7 +22,7 @@ pub fn init_logger() -> env_logger::Builder {
     builder.write_style(env_logger::WriteStyle::Always);
 
     // Custom Formatter for Github Actions
-    if std::env::var("CI").is_ok() {
+    if is_ci() {
         builder.format(|buf, record| match record.level().as_str() {
             "DEBUG" => writeln!(buf, "::debug:: {}", record.args()),
             "WARN" => writeln!(buf, "::warning:: {}", record.args()),
@@ -41,13 +52,83 @@ fn get_log_level() -> log::LevelFilter {
     log::LevelFilter::Info
 }
 
+/// Whether the output of the process runners is split into log groups, see '--group' and '--no-group'.
+static GROUPING: OnceLock<bool> = OnceLock::new();
+
+/// Force the log grouping of the process runners output on or off.
+pub fn set_grouping(enabled: bool) {
+    if GROUPING.set(enabled).is_err() {
+        warn!("Log grouping is already set");
+    }
+}
+
+/// Return true if the output of the process runners is split into log groups, by default only in CI.
+pub fn is_grouping_enabled() -> bool {
+    *GROUPING.get_or_init(is_ci)
+}
+
+/// Progress events written to the events file
+#[derive(Clone, Copy)]
+pub enum Event {
+    /// A step has started.
+    Start,
+    /// A step finished successfully (or has been skipped).
+    Finish,
+    /// A step failed.
+    Failure,
+}
+
+impl Event {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Event::Start => "start",
+            Event::Finish => "finish",
+            Event::Failure => "failure",
+        }
+    }
+}
+
+/// Open the file where progress events are appended as JSON Lines.
+pub fn init_events(path: &Path) -> anyhow::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open events file {}: {}", path.display(), e))?;
+    EVENTS_SINK
+        .set(Mutex::new(file))
+        .map_err(|_| anyhow::anyhow!("Events file is already initialized"))
+}
+
+/// Append an event to the events file, this is a no-op if no events file has been set.
+pub fn emit_event(event: Event, command: &str, target: &str, status: &str) {
+    if let Some(sink) = EVENTS_SINK.get() {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let line = serde_json::json!({
+            "ts": ts,
+            "event": event.as_str(),
+            "command": command,
+            "target": target,
+            "status": status,
+        });
+        if let Ok(mut file) = sink.lock() {
+            if let Err(e) = writeln!(file, "{}", line) {
+                warn!("Failed to write event: {}", e);
+            }
+        }
+    }
+}
+
 /// Group Macro
 #[macro_export]
 macro_rules! group {
     // group!()
     ($($arg:tt)*) => {
         let title = format!($($arg)*);
-        if std::env::var("CI").is_ok() {
+        if $crate::utils::ci::is_ci() {
             println!("::group::{}", title)
         } else {
             log!(log::Level::Info, "{}", title)
@@ -60,7 +141,7 @@ macro_rules! group_info {
     // group_info!()
     ($($arg:tt)*) => {
         let title = format!($($arg)*);
-        if std::env::var("CI").is_ok() {
+        if $crate::utils::ci::is_ci() {
             println!("{}", title)
         } else {
             log!(log::Level::Info, "{}", title)
@@ -73,7 +154,7 @@ macro_rules! group_info {
 macro_rules! endgroup {
     // endgroup!()
     () => {
-        if std::env::var("CI").is_ok() {
+        if $crate::utils::ci::is_ci() {
             println!("::endgroup::")
         }
     };
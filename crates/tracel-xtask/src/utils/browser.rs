@@ -0,0 +1,34 @@
+use crate::utils::{ci::is_ci, process::run_process};
+
+/// Open a file or an URL in the default browser.
+/// On headless environments (CI or Linux without a display) the target is printed instead.
+pub fn open(target: &str) -> anyhow::Result<()> {
+    if is_headless() {
+        info!("No browser available, open the following location manually: {target}");
+        return Ok(());
+    }
+    let (program, args) = if cfg!(target_os = "windows") {
+        // the first argument of 'start' is the window title
+        ("cmd", vec!["/C", "start", "", target])
+    } else if cfg!(target_os = "macos") {
+        ("open", vec![target])
+    } else {
+        ("xdg-open", vec![target])
+    };
+    run_process(
+        program,
+        &args,
+        None,
+        None,
+        &format!("Failed to open {target}"),
+    )
+}
+
+fn is_headless() -> bool {
+    if is_ci() {
+        return true;
+    }
+    cfg!(all(unix, not(target_os = "macos")))
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
@@ -3,7 +3,7 @@ use std::process::Command;
 use anyhow::Ok;
 use regex::Regex;
 
-use crate::{endgroup, group, utils::process::run_process};
+use crate::{classify_error, endgroup, group, utils::process::run_process, XtaskError};
 
 /// Ensure that a cargo crate is installed
 pub fn ensure_cargo_crate_is_installed(
@@ -26,12 +26,16 @@ pub fn ensure_cargo_crate_is_installed(
         if let Some(version) = version {
             args.extend(vec!["--version", version]);
         }
-        run_process(
-            "cargo",
-            &args,
-            None,
-            None,
-            &format!("crate '{}' should be installed", crate_name),
+        classify_error(
+            run_process(
+                "cargo",
+                &args,
+                None,
+                None,
+                &format!("crate '{}' should be installed", crate_name),
+            ),
+            XtaskError::ToolNotInstalled,
+            &format!("Cannot install the crate '{}'", crate_name),
         )?;
         endgroup!();
     }
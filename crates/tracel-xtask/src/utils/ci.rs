@@ -0,0 +1,50 @@
+/// Continuous integration providers detected from the environment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CiProvider {
+    GitHub,
+    GitLab,
+    /// Any other provider setting the `CI` environment variable.
+    Generic,
+}
+
+/// Return the continuous integration provider executing xtask, if any.
+pub fn detect() -> Option<CiProvider> {
+    detect_from(|name| std::env::var(name).ok())
+}
+
+/// Return true when xtask is executed in a continuous integration environment.
+pub fn is_ci() -> bool {
+    detect().is_some()
+}
+
+fn detect_from(var: impl Fn(&str) -> Option<String>) -> Option<CiProvider> {
+    if var("GITHUB_ACTIONS").is_some_and(|v| v == "true") {
+        Some(CiProvider::GitHub)
+    } else if var("GITLAB_CI").is_some() {
+        Some(CiProvider::GitLab)
+    } else if var("CI").is_some() {
+        Some(CiProvider::Generic)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::github(&[("GITHUB_ACTIONS", "true"), ("CI", "true")], Some(CiProvider::GitHub))]
+    #[case::gitlab(&[("GITLAB_CI", "true"), ("CI", "true")], Some(CiProvider::GitLab))]
+    #[case::generic(&[("CI", "1")], Some(CiProvider::Generic))]
+    #[case::local(&[], None)]
+    fn test_detect_from(#[case] vars: &[(&str, &str)], #[case] expected: Option<CiProvider>) {
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(detect_from(var), expected);
+    }
+}
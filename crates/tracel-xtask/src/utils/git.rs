@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::errors::spawn_error;
+
+use super::workspace::{get_workspace_members, WorkspaceMember, WorkspaceMemberType};
+
+/// Run git with the given arguments and return its trimmed standard output.
+pub fn run_git(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| spawn_error("git", e, "Failed to execute git"))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Return the root directory of the git repository.
+pub fn git_repo_root() -> anyhow::Result<PathBuf> {
+    run_git(&["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Render PATH relative to the root of the git repository for log messages, the path is
+/// rendered as is when it is outside of the repository or when there is no repository.
+/// Prefer the absolute path in error messages where precision matters.
+pub fn display_relative_to_root(path: &Path) -> String {
+    static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    let root = ROOT.get_or_init(|| git_repo_root().ok());
+    let absolute = std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf());
+    match root {
+        Some(root) => relative_to(&absolute, root)
+            .unwrap_or(path)
+            .display()
+            .to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Return PATH relative to ROOT, None if PATH is not inside ROOT.
+fn relative_to<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    match path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => Some(Path::new(".")),
+        Ok(relative) => Some(relative),
+        Err(_) => None,
+    }
+}
+
+/// Return the directory where git looks for hooks, this takes 'core.hooksPath' into account.
+pub fn get_hooks_dir() -> anyhow::Result<PathBuf> {
+    run_git(&["rev-parse", "--git-path", "hooks"])
+        .map(PathBuf::from)
+        .map_err(|e| anyhow::anyhow!("Cannot find the git hooks directory: {}", e))
+}
+
+/// Return the absolute paths of the files changed since the given git reference,
+/// uncommitted changes and untracked files included. Deleted files are part of the list.
+pub fn changed_files_since(reference: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let root = git_repo_root()?;
+    let changed = run_git(&["diff", "--name-only", reference])?;
+    // new files are not known to 'git diff' until they are staged
+    let untracked = run_git(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--full-name",
+        ":/",
+    ])?;
+    let files: BTreeSet<&str> = changed.lines().chain(untracked.lines()).collect();
+    Ok(files.into_iter().map(|f| root.join(f)).collect())
+}
+
+/// Return the names of the workspace members with changed files since the given git reference,
+/// uncommitted changes and untracked files included.
+pub fn changed_packages_since(reference: &str) -> anyhow::Result<Vec<String>> {
+    let files = changed_files_since(reference)?;
+    Ok(workspace_files_by_package(&files).into_keys().collect())
+}
+
+/// Group the given files by the name of the workspace member owning them.
+/// Files outside of the workspace members are left out.
+pub fn workspace_files_by_package(files: &[PathBuf]) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut members = get_workspace_members(WorkspaceMemberType::Crate);
+    members.extend(get_workspace_members(WorkspaceMemberType::Example));
+    files_by_package(&members, files)
+}
+
+/// Group the files by the members owning them, a file is owned by the member with the deepest
+/// directory containing it so that nested packages are handled correctly.
+fn files_by_package(
+    members: &[WorkspaceMember],
+    files: &[PathBuf],
+) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut packages: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for file in files {
+        let owner = members
+            .iter()
+            .filter(|m| file.starts_with(Path::new(&m.path)))
+            .max_by_key(|m| Path::new(&m.path).components().count());
+        if let Some(owner) = owner {
+            packages
+                .entry(owner.name.clone())
+                .or_default()
+                .push(file.clone());
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn members() -> Vec<WorkspaceMember> {
+        vec![
+            WorkspaceMember {
+                name: "root".to_string(),
+                path: "/repo".to_string(),
+            },
+            WorkspaceMember {
+                name: "foo".to_string(),
+                path: "/repo/crates/foo".to_string(),
+            },
+            WorkspaceMember {
+                name: "foo-macros".to_string(),
+                path: "/repo/crates/foo/macros".to_string(),
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::no_files(&[], &[])]
+    #[case::single_crate(&["/repo/crates/foo/src/lib.rs"], &["foo"])]
+    #[case::nested_crate(&["/repo/crates/foo/macros/src/lib.rs"], &["foo-macros"])]
+    #[case::root_package(&["/repo/src/main.rs"], &["root"])]
+    #[case::deduplicated(
+        &["/repo/crates/foo/src/lib.rs", "/repo/crates/foo/Cargo.toml", "/repo/src/main.rs"],
+        &["foo", "root"]
+    )]
+    #[case::same_prefix_not_a_directory(&["/repo/crates/foobar/src/lib.rs"], &["root"])]
+    #[case::outside_of_workspace(&["/other/file.rs"], &[])]
+    fn test_files_by_package_owners(#[case] files: &[&str], #[case] expected: &[&str]) {
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        let packages: Vec<String> = files_by_package(&members(), &files).into_keys().collect();
+        assert_eq!(packages, expected);
+    }
+
+    #[rstest]
+    #[case::inside("/repo/crates/foo/Cargo.toml", Some("crates/foo/Cargo.toml"))]
+    #[case::root("/repo", Some("."))]
+    #[case::outside("/other/Cargo.toml", None)]
+    #[case::same_prefix_not_a_directory("/repository/Cargo.toml", None)]
+    fn test_relative_to(#[case] path: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            relative_to(Path::new(path), Path::new("/repo")),
+            expected.map(Path::new)
+        );
+    }
+
+    #[test]
+    fn test_files_by_package_groups_files() {
+        let files: Vec<PathBuf> = [
+            "/repo/crates/foo/src/lib.rs",
+            "/repo/src/main.rs",
+            "/repo/crates/foo/src/main.rs",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let packages = files_by_package(&members(), &files);
+        assert_eq!(packages["foo"], [files[0].clone(), files[2].clone()]);
+        assert_eq!(packages["root"], [files[1].clone()]);
+    }
+}
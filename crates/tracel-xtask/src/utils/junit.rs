@@ -0,0 +1,264 @@
+use std::{path::Path, sync::Mutex};
+
+use super::git::display_relative_to_root;
+use serde_json::Value;
+
+/// Test suites collected from the libtest JSON events, None when no JUnit report is requested.
+static JUNIT_SUITES: Mutex<Option<Vec<TestSuite>>> = Mutex::new(None);
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum TestStatus {
+    Passed,
+    /// Failed with the captured output of the test
+    Failed(String),
+    Ignored,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TestCase {
+    pub name: String,
+    pub status: TestStatus,
+    pub time: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TestSuite {
+    pub name: String,
+    pub cases: Vec<TestCase>,
+    pub time: f64,
+}
+
+/// Start collecting the libtest JSON events of the executed processes.
+pub(crate) fn enable_junit() {
+    *JUNIT_SUITES
+        .lock()
+        .expect("junit suites lock should not be poisoned") = Some(vec![]);
+}
+
+/// Stop collecting the libtest JSON events and return the collected test suites.
+pub(crate) fn take_junit_suites() -> Vec<TestSuite> {
+    JUNIT_SUITES
+        .lock()
+        .expect("junit suites lock should not be poisoned")
+        .take()
+        .unwrap_or_default()
+}
+
+/// Discard the collected test suites named NAMES, used for retried test binaries.
+pub(crate) fn remove_junit_suites(names: &[String]) {
+    if let Some(suites) = JUNIT_SUITES
+        .lock()
+        .expect("junit suites lock should not be poisoned")
+        .as_mut()
+    {
+        suites.retain(|suite| !names.contains(&suite.name));
+    }
+}
+
+/// Record a libtest JSON event line in the test suite named `suite`.
+/// Return the human readable output of the event, possibly empty, or None if the JUnit report
+/// is not enabled or the line is not a libtest JSON event.
+pub(crate) fn record_junit_event(line: &str, suite: &str) -> Option<String> {
+    let mut guard = JUNIT_SUITES
+        .lock()
+        .expect("junit suites lock should not be poisoned");
+    let suites = guard.as_mut()?;
+    apply_event(suites, line, suite)
+}
+
+fn apply_event(suites: &mut Vec<TestSuite>, line: &str, suite: &str) -> Option<String> {
+    let event: Value = serde_json::from_str(line.trim()).ok()?;
+    let kind = event["event"].as_str()?;
+    match (event["type"].as_str()?, kind) {
+        ("suite", "started") => {
+            suites.push(TestSuite {
+                name: suite.to_string(),
+                cases: vec![],
+                time: 0.0,
+            });
+            Some(format!(
+                "running {} tests",
+                event["test_count"].as_u64().unwrap_or(0)
+            ))
+        }
+        ("suite", _) => {
+            let time = event["exec_time"].as_f64().unwrap_or(0.0);
+            if let Some(current) = suites.last_mut() {
+                current.time = time;
+            }
+            Some(format!(
+                "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out; finished in {:.2}s",
+                if kind == "ok" { "ok" } else { "FAILED" },
+                event["passed"].as_u64().unwrap_or(0),
+                event["failed"].as_u64().unwrap_or(0),
+                event["ignored"].as_u64().unwrap_or(0),
+                event["measured"].as_u64().unwrap_or(0),
+                event["filtered_out"].as_u64().unwrap_or(0),
+                time,
+            ))
+        }
+        ("test", "started") => Some(String::new()),
+        ("test", _) => {
+            let name = event["name"].as_str()?.to_string();
+            let (status, output) = match kind {
+                "ok" => (TestStatus::Passed, format!("test {name} ... ok")),
+                "ignored" => (TestStatus::Ignored, format!("test {name} ... ignored")),
+                _ => {
+                    let stdout = event["stdout"].as_str().unwrap_or_default().to_string();
+                    let mut output = format!("test {name} ... FAILED");
+                    if !stdout.is_empty() {
+                        output = format!("{output}\n{}", stdout.trim_end());
+                    }
+                    (TestStatus::Failed(stdout), output)
+                }
+            };
+            if let Some(current) = suites.last_mut() {
+                current.cases.push(TestCase {
+                    name,
+                    status,
+                    time: event["exec_time"].as_f64().unwrap_or(0.0),
+                });
+            }
+            Some(output)
+        }
+        _ => None,
+    }
+}
+
+/// Write the given test suites as a JUnit XML report to `path`.
+pub(crate) fn write_junit_report(path: &Path, suites: &[TestSuite]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, render_junit(suites))
+        .map_err(|e| anyhow::anyhow!("Failed to write JUnit report {}: {}", path.display(), e))?;
+    info!("JUnit report written to {}", display_relative_to_root(path));
+    Ok(())
+}
+
+fn render_junit(suites: &[TestSuite]) -> String {
+    let count = |suite: &TestSuite, failed: bool| {
+        suite
+            .cases
+            .iter()
+            .filter(|c| match c.status {
+                TestStatus::Failed(_) => failed,
+                TestStatus::Ignored => !failed,
+                TestStatus::Passed => false,
+            })
+            .count()
+    };
+    let tests: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let failures: usize = suites.iter().map(|s| count(s, true)).sum();
+    let skipped: usize = suites.iter().map(|s| count(s, false)).sum();
+    let time: f64 = suites.iter().map(|s| s.time).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{time:.3}\">\n"
+    ));
+    for suite in suites {
+        let name = escape_xml(&suite.name);
+        xml.push_str(&format!(
+            "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            suite.cases.len(),
+            count(suite, true),
+            count(suite, false),
+            suite.time
+        ));
+        for case in &suite.cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{name}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                case.time
+            );
+            match &case.status {
+                TestStatus::Passed => xml.push_str(&format!("{open}/>\n")),
+                TestStatus::Ignored => xml.push_str(&format!("{open}>\n      <skipped/>\n    </testcase>\n")),
+                TestStatus::Failed(output) => xml.push_str(&format!(
+                    "{open}>\n      <failure message=\"test failed\">{}</failure>\n    </testcase>\n",
+                    escape_xml(output)
+                )),
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::not_json("running 2 tests", None)]
+    #[case::unknown_type(r#"{ "type": "bench", "name": "b", "event": "ok" }"#, None)]
+    #[case::suite_started(
+        r#"{ "type": "suite", "event": "started", "test_count": 2 }"#,
+        Some("running 2 tests")
+    )]
+    #[case::test_started(r#"{ "type": "test", "event": "started", "name": "a::b" }"#, Some(""))]
+    #[case::test_ok(
+        r#"{ "type": "test", "name": "a::b", "event": "ok", "exec_time": 0.5 }"#,
+        Some("test a::b ... ok")
+    )]
+    #[case::test_failed(
+        r#"{ "type": "test", "name": "a::c", "event": "failed", "stdout": "boom\n" }"#,
+        Some("test a::c ... FAILED\nboom")
+    )]
+    #[case::suite_finished(
+        r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.25 }"#,
+        Some("test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.25s")
+    )]
+    fn test_apply_event(#[case] line: &str, #[case] expected: Option<&str>) {
+        let mut suites = vec![];
+        apply_event(
+            &mut suites,
+            r#"{ "type": "suite", "event": "started", "test_count": 2 }"#,
+            "my_crate",
+        );
+        assert_eq!(
+            apply_event(&mut suites, line, "my_crate").as_deref(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let mut suites = vec![];
+        [
+            r#"{ "type": "suite", "event": "started", "test_count": 3 }"#,
+            r#"{ "type": "test", "name": "ok_test", "event": "ok", "exec_time": 0.5 }"#,
+            r#"{ "type": "test", "name": "bad<test>", "event": "failed", "exec_time": 0.25, "stdout": "a & b" }"#,
+            r#"{ "type": "test", "name": "skipped_test", "event": "ignored" }"#,
+            r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "exec_time": 1.0 }"#,
+        ]
+        .iter()
+        .for_each(|line| {
+            apply_event(&mut suites, line, "my_crate");
+        });
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="3" failures="1" skipped="1" time="1.000">
+  <testsuite name="my_crate" tests="3" failures="1" skipped="1" time="1.000">
+    <testcase name="ok_test" classname="my_crate" time="0.500"/>
+    <testcase name="bad&lt;test&gt;" classname="my_crate" time="0.250">
+      <failure message="test failed">a &amp; b</failure>
+    </testcase>
+    <testcase name="skipped_test" classname="my_crate" time="0.000">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(render_junit(&suites), expected);
+    }
+}
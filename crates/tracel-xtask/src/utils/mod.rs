@@ -1,11 +1,17 @@
 use std::process::Command;
 
+pub mod browser;
 pub mod cargo;
+pub mod ci;
+pub mod git;
 pub mod helpers;
+pub(crate) mod junit;
+pub mod output;
 pub mod process;
 pub mod prompt;
 pub mod rustup;
 pub mod time;
+pub mod tool;
 pub mod workspace;
 
 pub fn get_command_line_from_command(command: &Command) -> String {
@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+
+use serde_json::Value;
+use strum::{Display, EnumString};
+
+/// Output mode of the command results, see '--output'.
+static OUTPUT_MODE: OnceLock<OutputMode> = OnceLock::new();
+
+#[derive(EnumString, Display, Default, Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputMode {
+    /// Human readable output.
+    #[default]
+    Human,
+    /// Machine readable JSON output.
+    Json,
+}
+
+/// Set the output mode of the command results.
+pub fn set_output_mode(mode: OutputMode) -> anyhow::Result<()> {
+    OUTPUT_MODE
+        .set(mode)
+        .map_err(|_| anyhow::anyhow!("Output mode is already set"))
+}
+
+/// Return the output mode of the command results, human readable by default.
+pub fn output_mode() -> OutputMode {
+    OUTPUT_MODE.get().copied().unwrap_or_default()
+}
+
+/// Result of a command that can be printed either for humans or as JSON.
+pub trait CommandResult {
+    /// Human readable representation of the result.
+    fn to_human(&self) -> String;
+    /// JSON representation of the result.
+    fn to_json(&self) -> Value;
+}
+
+/// Print the result on stdout with the given output mode.
+pub fn print_result(value: &impl CommandResult, mode: OutputMode) {
+    println!("{}", render_result(value, mode));
+}
+
+fn render_result(value: &impl CommandResult, mode: OutputMode) -> String {
+    match mode {
+        OutputMode::Human => value.to_human(),
+        OutputMode::Json => serde_json::to_string_pretty(&value.to_json())
+            .expect("JSON value should be serializable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    struct Answer;
+
+    impl CommandResult for Answer {
+        fn to_human(&self) -> String {
+            "The answer is 42".to_string()
+        }
+
+        fn to_json(&self) -> Value {
+            serde_json::json!({ "answer": 42 })
+        }
+    }
+
+    #[rstest]
+    #[case::human(OutputMode::Human, "The answer is 42")]
+    #[case::json(OutputMode::Json, "{\n  \"answer\": 42\n}")]
+    fn test_render_result(#[case] mode: OutputMode, #[case] expected: &str) {
+        assert_eq!(render_result(&Answer, mode), expected);
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     io::{BufRead, BufReader},
     path::Path,
     process::{Command, Stdio},
-    sync::mpsc,
+    sync::{mpsc, Arc, Mutex, OnceLock},
     thread,
 };
 
@@ -11,9 +11,237 @@ use anyhow;
 use rand::Rng;
 use regex::Regex;
 
+use crate::errors::{process_failed, spawn_error};
 use crate::group_info;
+use crate::logging::{emit_event, is_grouping_enabled, Event};
+use crate::utils::junit::{record_junit_event, remove_junit_suites};
 use crate::{endgroup, group};
 
+/// Extra arguments appended to the cargo invocations of the process runners, see '--cargo-arg'.
+static CARGO_EXTRA_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Set the extra arguments to append to the cargo arguments of the process runners.
+pub fn set_cargo_extra_args(args: Vec<String>) -> anyhow::Result<()> {
+    CARGO_EXTRA_ARGS
+        .set(args)
+        .map_err(|_| anyhow::anyhow!("Extra cargo arguments are already set"))
+}
+
+/// Insert the extra cargo arguments before the binary args separator '--'.
+pub(crate) fn with_cargo_extra_args<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    let (cargo_args, binary_args) = split_vector(args, "--");
+    let mut result = cargo_args.to_vec();
+    if let Some(extra) = CARGO_EXTRA_ARGS.get() {
+        result.extend(extra.iter().map(String::as_str));
+    }
+    result.extend(binary_args);
+    result
+}
+
+/// Lockfile and network flags of a command passed to its cargo invocations, see '--locked',
+/// '--frozen' and '--offline'.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct CargoLockArgs {
+    pub(crate) locked: bool,
+    pub(crate) frozen: bool,
+    pub(crate) offline: bool,
+}
+
+impl CargoLockArgs {
+    pub(crate) fn new(locked: bool, frozen: bool, offline: bool) -> Self {
+        Self {
+            locked,
+            frozen,
+            offline,
+        }
+    }
+
+    /// Return the enabled flags.
+    fn flags(&self) -> Vec<&'static str> {
+        [
+            (self.locked, "--locked"),
+            (self.frozen, "--frozen"),
+            (self.offline, "--offline"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, flag)| enabled.then_some(flag))
+        .collect()
+    }
+
+    /// Insert the enabled flags in ARGS before the binary args separator '--'.
+    pub(crate) fn apply<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        let (cargo_args, binary_args) = split_vector(args, "--");
+        let mut result = cargo_args.to_vec();
+        result.extend(self.flags());
+        result.extend(binary_args);
+        result
+    }
+}
+
+/// Totals of the test results reported by the test harness
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TestTotals {
+    pub passed: u64,
+    pub failed: u64,
+    pub ignored: u64,
+}
+
+/// Test results accumulated from the output of the executed processes, None when the test
+/// results are not collected.
+static TEST_TOTALS: Mutex<Option<TestTotals>> = Mutex::new(None);
+
+/// Start collecting the test results reported by the executed processes.
+/// The output of the package processes is then piped instead of inherited.
+pub fn enable_test_totals() {
+    *TEST_TOTALS
+        .lock()
+        .expect("test totals lock should not be poisoned") = Some(TestTotals::default());
+}
+
+/// Stop collecting the test results and return the results accumulated since
+/// `enable_test_totals` was called.
+pub fn take_test_totals() -> TestTotals {
+    TEST_TOTALS
+        .lock()
+        .expect("test totals lock should not be poisoned")
+        .take()
+        .unwrap_or_default()
+}
+
+/// Return true if the test results of the executed processes are collected.
+fn is_collecting_test_totals() -> bool {
+    TEST_TOTALS
+        .lock()
+        .expect("test totals lock should not be poisoned")
+        .is_some()
+}
+
+/// Test results of each executed test binary, keyed by the name of the binary.
+static TEST_SUITE_TOTALS: Mutex<Vec<(String, TestTotals)>> = Mutex::new(vec![]);
+
+/// Forget the test results of the test binaries executed so far, the accumulated test results
+/// are kept.
+pub(crate) fn reset_test_suite_totals() {
+    TEST_SUITE_TOTALS
+        .lock()
+        .expect("test suite totals lock should not be poisoned")
+        .clear();
+}
+
+/// Return the names of the test binaries with failed tests since the last call to
+/// `reset_test_suite_totals`.
+pub(crate) fn failed_test_suites() -> Vec<String> {
+    let mut names: Vec<String> = TEST_SUITE_TOTALS
+        .lock()
+        .expect("test suite totals lock should not be poisoned")
+        .iter()
+        .filter(|(_, totals)| totals.failed > 0)
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Discard the test results of the test binaries named NAMES, used before re-running them.
+/// The results are removed from the accumulated test results and from the JUnit report.
+pub(crate) fn discard_test_suites(names: &[String]) {
+    let mut totals = TEST_TOTALS
+        .lock()
+        .expect("test totals lock should not be poisoned");
+    let mut suites = TEST_SUITE_TOTALS
+        .lock()
+        .expect("test suite totals lock should not be poisoned");
+    suites.retain(|(name, suite)| {
+        if !names.contains(name) {
+            return true;
+        }
+        if let Some(totals) = totals.as_mut() {
+            totals.passed -= suite.passed;
+            totals.failed -= suite.failed;
+            totals.ignored -= suite.ignored;
+        }
+        false
+    });
+    remove_junit_suites(names);
+}
+
+/// Add the counts of a test harness 'test result: ...' line reported by the test binary SUITE
+/// to the accumulated test results.
+fn record_test_result(line: &str, suite: &str) {
+    if let Some((passed, failed, ignored)) = parse_test_result(line) {
+        if let Some(totals) = TEST_TOTALS
+            .lock()
+            .expect("test totals lock should not be poisoned")
+            .as_mut()
+        {
+            totals.passed += passed;
+            totals.failed += failed;
+            totals.ignored += ignored;
+            TEST_SUITE_TOTALS
+                .lock()
+                .expect("test suite totals lock should not be poisoned")
+                .push((
+                    suite.to_string(),
+                    TestTotals {
+                        passed,
+                        failed,
+                        ignored,
+                    },
+                ));
+        }
+    }
+}
+
+/// Print a line of the standard output of a process and collect the test results it reports.
+/// libtest JSON events are recorded in the JUnit report of the test suite named `suite` and
+/// printed in the usual human readable format instead.
+fn echo_output_line(line: &str, suite: &str) {
+    match record_junit_event(line, suite) {
+        Some(output) => output.lines().for_each(|l| {
+            record_test_result(l, suite);
+            println!("{}", l);
+        }),
+        None => {
+            record_test_result(line, suite);
+            println!("{}", line);
+        }
+    }
+}
+
+/// Parse a test harness line like 'test result: ok. 3 passed; 0 failed; 1 ignored; ...'
+/// or a nextest summary line like 'Summary [ 0.1s] 4 tests run: 3 passed, 1 failed, 1 skipped'
+fn parse_test_result(line: &str) -> Option<(u64, u64, u64)> {
+    static TEST_RESULT_RX: OnceLock<Regex> = OnceLock::new();
+    let rx = TEST_RESULT_RX.get_or_init(|| {
+        Regex::new(r"test result: \S+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap()
+    });
+    static NEXTEST_SUMMARY_RX: OnceLock<Regex> = OnceLock::new();
+    let nextest_rx = NEXTEST_SUMMARY_RX.get_or_init(|| {
+        Regex::new(
+            r"Summary \[.*\] \d+ tests? run: (\d+) passed(?: \([^)]*\))?(?:, (\d+) failed)?.*?(?:, (\d+) skipped)?$",
+        )
+        .unwrap()
+    });
+    let line = remove_ansi_codes(line);
+    let count = |m: Option<regex::Match>| m.map_or(Some(0), |m| m.as_str().parse().ok());
+    let caps = rx.captures(&line).or_else(|| nextest_rx.captures(&line))?;
+    Some((
+        count(caps.get(1))?,
+        count(caps.get(2))?,
+        count(caps.get(3))?,
+    ))
+}
+
+/// Parse the name of the test binary executed by cargo from a line like
+/// 'Running unittests src/lib.rs (target/debug/deps/tracel_xtask-0123abcd)'.
+fn parse_test_suite(line: &str) -> Option<String> {
+    static SUITE_RX: OnceLock<Regex> = OnceLock::new();
+    let rx = SUITE_RX.get_or_init(|| Regex::new(r"Running .*(?:deps|examples)/([^-\s]+)").unwrap());
+    let line = standardize_slashes(&remove_ansi_codes(line));
+    rx.captures(&line).map(|caps| caps[1].to_string())
+}
+
 /// Run a process
 pub fn run_process(
     name: &str,
@@ -32,15 +260,14 @@ pub fn run_process(
         command.envs(&envs);
     }
     let status = command.args(args).status().map_err(|e| {
-        anyhow::anyhow!(
-            "Failed to execute {} {}: {}",
+        spawn_error(
             name,
-            args.first().unwrap(),
-            e
+            e,
+            &format!("Failed to execute {} {}", name, args.first().unwrap()),
         )
     })?;
     if !status.success() {
-        return Err(anyhow::anyhow!("{}", error_msg));
+        return Err(process_failed(error_msg));
     }
     anyhow::Ok(())
 }
@@ -51,6 +278,7 @@ pub fn run_process(
 pub fn run_process_for_workspace<'a>(
     name: &str,
     args: &[&'a str],
+    envs: Option<HashMap<&str, &str>>,
     excluded: &'a [String],
     group_regexp: Option<&str>,
     group_name: Option<&str>,
@@ -58,7 +286,9 @@ pub fn run_process_for_workspace<'a>(
     ignore_log: Option<&str>,
     ignore_msg: Option<&str>,
 ) -> anyhow::Result<()> {
-    let group_rx: Option<Regex> = group_regexp.map(|r| Regex::new(r).unwrap());
+    let group_rx: Option<Regex> = group_regexp
+        .filter(|_| is_grouping_enabled())
+        .map(|r| Regex::new(r).unwrap());
     // split the args between cargo args and binary args so that we can extend the cargo args
     // and then append the binary args back.
     let (cargo_args, binary_args) = split_vector(args, "--");
@@ -67,20 +297,26 @@ pub fn run_process_for_workspace<'a>(
         .iter()
         .for_each(|ex| cmd_args.extend(["--exclude", ex]));
     cmd_args.extend(binary_args);
+    let cmd_args = with_cargo_extra_args(&cmd_args);
+    let command_line = format!("{} {}", name, cmd_args.join(" "));
     group_info!("Command line: cargo {}", cmd_args.join(" "));
+    emit_event(Event::Start, &command_line, "workspace", "running");
     // process
-    let mut child = Command::new(name)
+    let mut command = Command::new(name);
+    if let Some(envs) = envs {
+        command.envs(&envs);
+    }
+    let mut child = command
         .args(&cmd_args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| {
-            anyhow::anyhow!(format!(
-                "Failed to start {} {}: {}",
+            spawn_error(
                 name,
-                cmd_args.first().unwrap(),
-                e
-            ))
+                e,
+                &format!("Failed to start {} {}", name, cmd_args.first().unwrap()),
+            )
         })?;
 
     // handle stdout and stderr in dedicated threads using a MPSC channel for synchronization
@@ -109,12 +345,22 @@ pub fn run_process_for_workspace<'a>(
     drop(tx);
 
     // Process the stdout to inject log groups
+    let mut suite = "workspace".to_string();
     let mut ignore_error = false;
     let mut close_group = false;
-    for (line, _is_stderr) in rx.iter() {
+    for (line, is_stderr) in rx.iter() {
         let mut skip_line = false;
 
-        if let Some(rx) = &group_rx {
+        // name of the test binary to report the test results of the following lines
+        if is_stderr {
+            if let Some(name) = parse_test_suite(&line) {
+                suite = name;
+            }
+        }
+
+        // cargo reports the executed binaries on stderr, looking for groups only in stderr
+        // prevents the output of the tests (for instance with --nocapture) to open new groups
+        if let (Some(rx), true) = (&group_rx, is_stderr) {
             let cleaned_line = standardize_slashes(&remove_ansi_codes(&line));
             if let Some(caps) = rx.captures(&cleaned_line) {
                 let crate_name = &caps[1];
@@ -137,7 +383,13 @@ pub fn run_process_for_workspace<'a>(
         }
 
         if !skip_line {
-            println!("{}", line);
+            if is_stderr {
+                // nextest reports its summary on stderr
+                record_test_result(&line, &suite);
+                println!("{}", line);
+            } else {
+                echo_output_line(&line, &suite);
+            }
         }
     }
 
@@ -149,9 +401,11 @@ pub fn run_process_for_workspace<'a>(
         if close_group {
             endgroup!();
         }
+        emit_event(Event::Finish, &command_line, "workspace", "success");
         anyhow::Ok(())
     } else {
-        Err(anyhow::anyhow!("{}", error_msg))
+        emit_event(Event::Failure, &command_line, "workspace", "failure");
+        Err(process_failed(error_msg))
     }
 }
 
@@ -161,38 +415,110 @@ pub fn run_process_for_package(
     name: &str,
     package: &String,
     args: &[&str],
+    envs: Option<HashMap<&str, &str>>,
     excluded: &[String],
     only: &[String],
     error_msg: &str,
     ignore_log: Option<&str>,
     ignore_msg: Option<&str>,
 ) -> anyhow::Result<()> {
+    let args = with_cargo_extra_args(args);
+    let joined_args = args.join(" ");
+    let command_line = format!("{} {}", name, joined_args);
     if excluded.contains(package) || (!only.is_empty() && !only.contains(package)) {
         group_info!("Skip '{}' because it has been excluded!", package);
+        emit_event(Event::Finish, &command_line, package, "skipped");
         return anyhow::Ok(());
     }
-    let joined_args = args.join(" ");
     group_info!("Command line: cargo {}", &joined_args);
-    let output = Command::new("cargo")
+    emit_event(Event::Start, &command_line, package, "running");
+    let mut command = Command::new("cargo");
+    if let Some(envs) = envs {
+        command.envs(&envs);
+    }
+    if !is_collecting_test_totals() {
+        let status = command
+            .args(&args)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .map_err(|e| {
+                spawn_error(
+                    name,
+                    e,
+                    &format!("Failed to execute process for '{}'", name),
+                )
+            })?;
+        if status.success() {
+            emit_event(Event::Finish, &command_line, package, "success");
+            return anyhow::Ok(());
+        }
+        emit_event(Event::Failure, &command_line, package, "failure");
+        return Err(process_failed(error_msg));
+    }
+    // the output is piped to collect the test results, see 'enable_test_totals'
+    let mut child = command
         .args(args)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            spawn_error(
+                name,
+                e,
+                &format!("Failed to execute process for '{}'", name),
+            )
+        })?;
+    // name of the test binary reporting the test results, cargo reports it on stderr
+    let suite = Arc::new(Mutex::new(package.clone()));
+    // stderr is echoed and kept to look for the ignore log once the process is finished
+    let stderr_thread = child.stderr.take().map(|stderr| {
+        let suite = Arc::clone(&suite);
+        thread::spawn(move || {
+            let mut content = String::new();
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some(name) = parse_test_suite(&line) {
+                    *suite.lock().expect("suite lock should not be poisoned") = name;
+                }
+                eprintln!("{}", line);
+                content.push_str(&line);
+                content.push('\n');
+            }
+            content
+        })
+    });
+    // stdout is echoed line by line to collect the test results
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let suite = suite
+                .lock()
+                .expect("suite lock should not be poisoned")
+                .clone();
+            echo_output_line(&line, &suite);
+        }
+    }
+    let status = child
+        .wait()
         .map_err(|e| anyhow::anyhow!("Failed to execute process for '{}': {}", name, e))?;
+    let stderr = stderr_thread
+        .and_then(|t| t.join().ok())
+        .unwrap_or_default();
 
-    if output.status.success() {
+    if status.success() {
+        emit_event(Event::Finish, &command_line, package, "success");
         return anyhow::Ok(());
     } else if let Some(log) = ignore_log {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         if stderr.contains(log) {
             if let Some(msg) = ignore_msg {
                 warn!("{}", msg);
             }
             endgroup!();
+            emit_event(Event::Finish, &command_line, package, "success");
             return anyhow::Ok(());
         }
     }
-    Err(anyhow::anyhow!("{}", error_msg))
+    emit_event(Event::Failure, &command_line, package, "failure");
+    Err(process_failed(error_msg))
 }
 
 /// Return a random port between 3000 and 9999
@@ -201,8 +527,9 @@ pub fn random_port() -> u16 {
     rng.gen_range(3000..=9999)
 }
 
-fn remove_ansi_codes(s: &str) -> String {
-    let re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+pub(crate) fn remove_ansi_codes(s: &str) -> String {
+    static ANSI_CODE_RX: OnceLock<Regex> = OnceLock::new();
+    let re = ANSI_CODE_RX.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap());
     re.replace_all(s, "").to_string()
 }
 
@@ -212,7 +539,7 @@ fn standardize_slashes(s: &str) -> String {
 
 /// Split given VEC into a left and right vectors where SPLIT belongs to the right vector.
 /// If SPLIT does not exist in VEC then left is a VEC slice and right is empty.
-fn split_vector<T: PartialEq>(vec: &[T], split: T) -> (&[T], &[T]) {
+pub(crate) fn split_vector<T: PartialEq>(vec: &[T], split: T) -> (&[T], &[T]) {
     let mut left = vec;
     let mut right = &vec[vec.len()..];
     if let Some(pos) = vec.iter().position(|e| *e == split) {
@@ -265,6 +592,56 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case::passed("test result: ok. 12 passed; 0 failed; 3 ignored; 0 measured; 0 filtered out; finished in 0.03s", Some((12, 0, 3)))]
+    #[case::failed("test result: FAILED. 10 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out", Some((10, 2, 0)))]
+    #[case::colored("test result: \x1b[32mok\x1b[0m. 1 passed; 0 failed; 0 ignored; 0 measured", Some((1, 0, 0)))]
+    #[case::nextest_passed("     Summary [   0.012s] 12 tests run: 12 passed, 3 skipped", Some((12, 0, 3)))]
+    #[case::nextest_failed("     Summary [   0.012s] 12 tests run: 10 passed, 2 failed, 0 skipped", Some((10, 2, 0)))]
+    #[case::nextest_flaky("     Summary [   0.012s] 2 tests run: 2 passed (1 flaky), 0 skipped", Some((2, 0, 0)))]
+    #[case::other_line("test tests::test_split_vector ... ok", None)]
+    fn test_parse_test_result(#[case] line: &str, #[case] expected: Option<(u64, u64, u64)>) {
+        assert_eq!(parse_test_result(line), expected);
+    }
+
+    #[rstest]
+    #[case::lib(
+        "     Running unittests src/lib.rs (target/debug/deps/tracel_xtask-0123abcd)",
+        Some("tracel_xtask")
+    )]
+    #[case::integration(
+        "     Running tests/bump.rs (target/debug/deps/bump-0123abcd)",
+        Some("bump")
+    )]
+    #[case::example(
+        "     Running unittests examples/demo.rs (target/debug/examples/demo-0123abcd)",
+        Some("demo")
+    )]
+    #[case::windows(
+        "     Running tests\\bump.rs (target\\debug\\deps\\bump-0123abcd.exe)",
+        Some("bump")
+    )]
+    #[case::other_line("   Compiling tracel-xtask v1.1.8", None)]
+    fn test_parse_test_suite(#[case] line: &str, #[case] expected: Option<&str>) {
+        assert_eq!(parse_test_suite(line).as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case::none(CargoLockArgs::default(), &["build", "--", "--nocapture"], &["build", "--", "--nocapture"])]
+    #[case::locked(CargoLockArgs::new(true, false, false), &["build"], &["build", "--locked"])]
+    #[case::all_flags(
+        CargoLockArgs::new(true, true, true),
+        &["clippy", "--", "--deny", "warnings"],
+        &["clippy", "--locked", "--frozen", "--offline", "--", "--deny", "warnings"]
+    )]
+    fn test_cargo_lock_args_apply(
+        #[case] lock_args: CargoLockArgs,
+        #[case] args: &[&str],
+        #[case] expected: &[&str],
+    ) {
+        assert_eq!(lock_args.apply(args), expected);
+    }
+
     #[rstest]
     #[case::element_found(vec!["a", "b", "c", "d", "e", "f"], "d", vec!["a", "b", "c"], vec!["d", "e", "f"])]
     #[case::element_not_found(vec!["a", "b", "c", "d", "e", "f"], "z", vec!["a", "b", "c", "d", "e", "f"], vec![])]
@@ -1,12 +1,82 @@
-use std::io::{self, Write};
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
+use super::ci::is_ci;
+
+/// Answer yes to all the confirmations without prompting, see '--yes'.
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Answer yes to all the subsequent confirmations without prompting.
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+/// Ask the user to confirm, in CI there is nobody to answer so the answer is always no.
 pub fn ask_once(prompt: &str) -> bool {
-    print!("{}\nDo you want to proceed? (yes/no): ", prompt);
-    io::stdout().flush().expect("stdout should be flushed");
-
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("should be able to read stdin line");
-    input.trim().to_lowercase() == "yes" || input.trim().to_lowercase() == "y"
+    confirm(&format!("{}\nDo you want to proceed?", prompt), false).unwrap_or(false)
+}
+
+/// Ask the user a yes/no question, an empty answer selects `default`.
+/// The answer is yes when '--yes' is set and `default` when nobody can answer, either in CI or
+/// when stdin is not a terminal.
+pub fn confirm(prompt: &str, default: bool) -> anyhow::Result<bool> {
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        info!("{} yes (--yes)", prompt);
+        return Ok(true);
+    }
+    if is_ci() || !io::stdin().is_terminal() {
+        warn!(
+            "{}\nCannot ask for confirmation in a non-interactive session, assuming {}.",
+            prompt,
+            if default { "yes" } else { "no" }
+        );
+        return Ok(default);
+    }
+    let choices = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        print!("{} {}: ", prompt, choices);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            // end of input
+            return Ok(default);
+        }
+        match parse_answer(&input, default) {
+            Some(answer) => return Ok(answer),
+            None => println!("Please answer 'yes' or 'no'."),
+        }
+    }
+}
+
+fn parse_answer(input: &str, default: bool) -> Option<bool> {
+    match input.trim().to_lowercase().as_str() {
+        "" => Some(default),
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::empty_default_no("\n", false, Some(false))]
+    #[case::empty_default_yes("  \n", true, Some(true))]
+    #[case::short_yes("y\n", false, Some(true))]
+    #[case::long_yes_uppercase(" YES \n", false, Some(true))]
+    #[case::short_no("n\n", true, Some(false))]
+    #[case::long_no("No\n", true, Some(false))]
+    #[case::invalid("maybe\n", true, None)]
+    fn test_parse_answer(
+        #[case] input: &str,
+        #[case] default: bool,
+        #[case] expected: Option<bool>,
+    ) {
+        assert_eq!(parse_answer(input, default), expected);
+    }
 }
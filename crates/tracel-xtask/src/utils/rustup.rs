@@ -42,17 +42,12 @@ pub fn rustup_get_installed_targets() -> String {
 
 /// Returns true if the current toolchain is the nightly
 pub fn is_current_toolchain_nightly() -> bool {
-    let output = Command::new("rustup")
-        .arg("show")
+    // 'rustup show' no longer prints the rustc version of the active toolchain in recent
+    // versions of rustup so we ask rustc directly
+    let output = Command::new("rustc")
+        .arg("--version")
         .output()
-        .expect("Should get the list of installed Rust toolchains");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    for line in output_str.lines() {
-        // look for the "rustc.*-nightly" line
-        if line.contains("rustc") && line.contains("-nightly") {
-            return true;
-        }
-    }
-    // assume we are using a stable toolchain if we did not find the nightly compiler
-    false
+        .expect("Should get the version of the Rust compiler");
+    // assume we are using a stable toolchain if the compiler version is not a nightly one
+    String::from_utf8_lossy(&output.stdout).contains("-nightly")
 }
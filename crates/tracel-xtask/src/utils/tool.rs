@@ -0,0 +1,97 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use semver::Version;
+
+use crate::XtaskError;
+
+/// Ensure that the binary NAME is available on the PATH, the returned error contains
+/// the INSTALL_HINT to help the user install the missing tool.
+pub fn ensure_tool_available(name: &str, install_hint: &str) -> anyhow::Result<()> {
+    if find_tool(name).is_none() {
+        return Err(XtaskError::tool_missing(name, install_hint).into());
+    }
+    Ok(())
+}
+
+/// Ensure that the binary NAME is available and that its version is at least MIN.
+pub fn ensure_tool_version(name: &str, min: &Version) -> anyhow::Result<()> {
+    ensure_tool_available(name, &format!("Install '{name}' {min} or later."))?;
+    match tool_version(name) {
+        Some(version) if version >= *min => Ok(()),
+        Some(version) => Err(XtaskError::ToolNotInstalled(format!(
+            "'{name}' {version} is too old, version {min} or later is required."
+        ))
+        .into()),
+        None => Err(XtaskError::ToolNotInstalled(format!(
+            "Cannot determine the version of '{name}', version {min} or later is required."
+        ))
+        .into()),
+    }
+}
+
+/// Return the version reported by `NAME --version` if it can be parsed.
+pub fn tool_version(name: &str) -> Option<Version> {
+    let output = Command::new(name).arg("--version").output().ok()?;
+    // some tools print their version on stderr
+    parse_tool_version(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| parse_tool_version(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Parse the first version number found in OUTPUT, missing patch numbers default to 0.
+fn parse_tool_version(output: &str) -> Option<Version> {
+    static VERSION_RX: OnceLock<Regex> = OnceLock::new();
+    let rx = VERSION_RX.get_or_init(|| {
+        Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").expect("should compile version regex")
+    });
+    let caps = rx.captures(output)?;
+    Some(Version::new(
+        caps[1].parse().ok()?,
+        caps[2].parse().ok()?,
+        caps.get(3).map_or(Some(0), |p| p.as_str().parse().ok())?,
+    ))
+}
+
+/// Return the path of the binary NAME if it is found in one of the PATH directories.
+pub fn find_tool(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths).find_map(|dir| find_tool_in_dir(name, &dir))
+}
+
+fn find_tool_in_dir(name: &str, dir: &Path) -> Option<PathBuf> {
+    let candidate = dir.join(name);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    // on Windows the executables are found by adding the extensions listed in PATHEXT
+    if cfg!(target_os = "windows") {
+        let extensions = env::var("PATHEXT").unwrap_or(".EXE;.CMD;.BAT".to_string());
+        return extensions
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| dir.join(format!("{name}{}", ext.to_lowercase())))
+            .find(|candidate| candidate.is_file());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::docker("Docker version 24.0.7, build afdd53b", Some(Version::new(24, 0, 7)))]
+    #[case::terraform("Terraform v1.6.0\non linux_amd64", Some(Version::new(1, 6, 0)))]
+    #[case::cargo_crate("typos-cli 1.24.1", Some(Version::new(1, 24, 1)))]
+    #[case::no_patch("tool 2.1", Some(Version::new(2, 1, 0)))]
+    #[case::no_version("unknown", None)]
+    fn test_parse_tool_version(#[case] output: &str, #[case] expected: Option<Version>) {
+        assert_eq!(parse_tool_version(output), expected);
+    }
+}
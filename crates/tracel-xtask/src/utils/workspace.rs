@@ -1,5 +1,10 @@
 use serde_json::Value;
-use std::{path::Path, process::Command};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
 
 const MEMBER_PATH_PREFIX: &str = if cfg!(target_os = "windows") {
     "path+file:///"
@@ -27,13 +32,7 @@ impl WorkspaceMember {
 
 /// Get workspace crates
 pub fn get_workspace_members(w_type: WorkspaceMemberType) -> Vec<WorkspaceMember> {
-    // Run `cargo metadata` command to get project metadata
-    let output = Command::new("cargo")
-        .arg("metadata")
-        .output()
-        .expect("Failed to execute command");
-    // Parse the JSON output
-    let metadata: Value = serde_json::from_slice(&output.stdout).expect("Failed to parse JSON");
+    let metadata = get_cargo_metadata();
     // Extract workspace members from the metadata
     let workspaces = metadata["workspace_members"]
         .as_array()
@@ -62,6 +61,138 @@ pub fn get_workspace_members(w_type: WorkspaceMemberType) -> Vec<WorkspaceMember
     workspaces
 }
 
+/// Target of a workspace member.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkspaceTarget {
+    pub package: String,
+    /// Kind of the target: 'lib', 'bin', 'test', 'example', 'bench' or 'custom-build'.
+    /// The library kinds like 'rlib' or 'proc-macro' are all reported as 'lib'.
+    pub kind: String,
+    pub name: String,
+}
+
+/// Get the targets of the workspace members.
+pub fn get_workspace_targets() -> Vec<WorkspaceTarget> {
+    let metadata = get_cargo_metadata();
+    let members: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .expect("Expected an array of workspace members")
+        .iter()
+        .filter_map(|m| m.as_str())
+        .collect();
+    metadata["packages"]
+        .as_array()
+        .expect("Expected an array of packages")
+        .iter()
+        .filter(|p| p["id"].as_str().is_some_and(|id| members.contains(&id)))
+        .flat_map(|p| {
+            let package = p["name"].as_str().unwrap_or_default();
+            p["targets"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(move |t| {
+                    let kind = match t["kind"].as_array()?.first()?.as_str()? {
+                        kind @ ("bin" | "test" | "example" | "bench" | "custom-build") => kind,
+                        _ => "lib",
+                    };
+                    Some(WorkspaceTarget {
+                        package: package.to_string(),
+                        kind: kind.to_string(),
+                        name: t["name"].as_str()?.to_string(),
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Get the names of the workspace members that can be published.
+/// A member is not publishable if its manifest sets `publish = false`.
+pub fn get_publishable_workspace_members() -> Vec<String> {
+    let metadata = get_cargo_metadata();
+    let members: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .expect("Expected an array of workspace members")
+        .iter()
+        .filter_map(|m| m.as_str())
+        .collect();
+    metadata["packages"]
+        .as_array()
+        .expect("Expected an array of packages")
+        .iter()
+        .filter(|p| p["id"].as_str().is_some_and(|id| members.contains(&id)))
+        .filter(|p| is_package_publishable(&p["publish"]))
+        .filter_map(|p| p["name"].as_str().map(|n| n.to_string()))
+        .collect()
+}
+
+/// Return the package names of the workspace keyed by their cargo package id
+pub fn get_package_names_by_id() -> HashMap<String, String> {
+    get_cargo_metadata()["packages"]
+        .as_array()
+        .expect("packages should be an array")
+        .iter()
+        .filter_map(|p| {
+            Some((
+                p["id"].as_str()?.to_string(),
+                p["name"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Return the Rust edition of the workspace packages keyed by their package name
+pub fn get_package_editions() -> HashMap<String, String> {
+    get_cargo_metadata()["packages"]
+        .as_array()
+        .expect("packages should be an array")
+        .iter()
+        .filter_map(|p| {
+            Some((
+                p["name"].as_str()?.to_string(),
+                p["edition"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Return the target directory of the workspace, it takes CARGO_TARGET_DIR into account
+pub fn get_target_directory() -> PathBuf {
+    get_cargo_metadata()["target_directory"]
+        .as_str()
+        .map(PathBuf::from)
+        .expect("target_directory should be a string")
+}
+
+/// Return the root directory of the workspace
+pub fn get_workspace_root() -> PathBuf {
+    get_cargo_metadata()["workspace_root"]
+        .as_str()
+        .map(PathBuf::from)
+        .expect("workspace_root should be a string")
+}
+
+/// Run `cargo metadata` command and parse its JSON output.
+/// The metadata is cached so that the command runs only once per xtask invocation.
+fn get_cargo_metadata() -> &'static Value {
+    static CARGO_METADATA: OnceLock<Value> = OnceLock::new();
+    CARGO_METADATA.get_or_init(|| {
+        let output = Command::new("cargo")
+            .arg("metadata")
+            .output()
+            .expect("Failed to execute command");
+        serde_json::from_slice(&output.stdout).expect("Failed to parse JSON")
+    })
+}
+
+/// `publish` is null when there is no restriction and an empty array when `publish = false`
+fn is_package_publishable(publish: &Value) -> bool {
+    match publish.as_array() {
+        Some(registries) => !registries.is_empty(),
+        None => true,
+    }
+}
+
 /// Legacy cargo metadata format for member specs (rust < 1.77)
 /// Example:
 /// "backend-comparison 0.13.0 (path+file:///Users/username/burn/backend-comparison)"
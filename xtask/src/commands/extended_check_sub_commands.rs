@@ -22,10 +22,7 @@ pub fn handle_command(args: ExtendedCheckArgsCmdArgs) -> anyhow::Result<()> {
                 .try_for_each(|c| {
                     handle_command(ExtendedCheckArgsCmdArgs {
                         command: Some(c),
-                        target: args.target.clone(),
-                        exclude: args.exclude.clone(),
-                        only: args.only.clone(),
-                        ignore_audit: args.ignore_audit,
+                        ..args.clone()
                     })
                 })
         }
@@ -50,6 +50,11 @@ pub fn handle_command(args: ExtendedFixCmdArgs, mut answer: Option<bool>) -> any
                             target: args.target.clone(),
                             exclude: args.exclude.clone(),
                             only: args.only.clone(),
+                            locked: args.locked,
+                            frozen: args.frozen,
+                            offline: args.offline,
+                            typos_config: args.typos_config.clone(),
+                            since: args.since.clone(),
                         },
                         Some(answer),
                     )
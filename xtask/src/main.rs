@@ -1,19 +1,24 @@
 mod commands;
 
+use std::process::ExitCode;
+
 use tracel_xtask::prelude::*;
 
 #[macro_use]
 extern crate log;
 
 #[macros::base_commands(
+    Bench,
     Bump,
     Build,
     Check,
+    Clean,
     Compile,
     Coverage,
     Doc,
     Dependencies,
     Fix,
+    InstallHooks,
     Publish,
     Test,
     Validate,
@@ -42,7 +47,12 @@ pub enum Command {
     ),
 }
 
-fn main() -> anyhow::Result<()> {
+fn main() -> ExitCode {
+    // exit with a dedicated code for each category of errors (see XtaskError)
+    to_exit_code(run())
+}
+
+fn run() -> anyhow::Result<()> {
     let args = init_xtask::<Command>()?;
     match args.command {
         Command::ExtendedBuildArgs(args) => commands::extended_build_args::handle_command(args),